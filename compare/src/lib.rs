@@ -0,0 +1,213 @@
+use std::time::{Duration, Instant};
+
+// One way of solving a part of a puzzle. Answers are compared as strings so that
+// strategies are free to return whatever type suits them. A strategy that only works on
+// some inputs, like a brute force with a bound, returns None when it can't tell.
+pub struct Strategy<I: ?Sized> {
+    pub part: u8,
+    pub name: &'static str,
+    pub run: fn(&I) -> Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub part: u8,
+    pub name: &'static str,
+    // None if the strategy was inconclusive
+    pub answer: Option<String>,
+    pub duration: Duration,
+}
+
+// Run every strategy against the same input
+pub fn compare<I: ?Sized>(input: &I, strategies: &[Strategy<I>]) -> Vec<Outcome> {
+    strategies
+        .iter()
+        .map(|strategy| {
+            let start = Instant::now();
+            let answer = (strategy.run)(input);
+            Outcome {
+                part: strategy.part,
+                name: strategy.name,
+                answer,
+                duration: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+// Parts whose strategies came up with different answers, ignoring inconclusive ones
+pub fn disagreements(outcomes: &[Outcome]) -> Vec<u8> {
+    let mut parts = outcomes.iter().map(|o| o.part).collect::<Vec<_>>();
+    parts.dedup();
+
+    parts
+        .into_iter()
+        .filter(|&part| {
+            let mut answers = outcomes
+                .iter()
+                .filter(|o| o.part == part)
+                .filter_map(|o| o.answer.as_ref());
+            let first = answers.next();
+            answers.any(|a| Some(a) != first)
+        })
+        .collect()
+}
+
+pub fn report(outcomes: &[Outcome]) -> String {
+    let disagreements = disagreements(outcomes);
+    let name_width = outcomes.iter().map(|o| o.name.len()).max().unwrap_or(0);
+    let answer = |o: &Outcome| o.answer.clone().unwrap_or(String::from("inconclusive"));
+    let answer_width = outcomes.iter().map(|o| answer(o).len()).max().unwrap_or(0);
+
+    let mut result = String::new();
+    let mut last_part = None;
+    for outcome in outcomes {
+        if last_part != Some(outcome.part) {
+            let status = if disagreements.contains(&outcome.part) {
+                "MISMATCH"
+            } else {
+                "ok"
+            };
+            result.push_str(&format!("Part{}: {}\n", outcome.part, status));
+            last_part = Some(outcome.part);
+        }
+        result.push_str(&format!(
+            "  {:name_width$}  {:answer_width$}  {:?}\n",
+            outcome.name,
+            answer(outcome),
+            outcome.duration
+        ));
+    }
+
+    result
+}
+
+// Compare the strategies if `--compare` was passed on the command line, exiting with an
+// error if any of them disagree. Returns false if no comparison was requested.
+pub fn start<I: ?Sized>(input: &I, strategies: &[Strategy<I>], args: &[String]) -> bool {
+    if !args.iter().any(|a| a == "--compare") {
+        return false;
+    }
+
+    let outcomes = compare(input, strategies);
+    print!("{}", report(&outcomes));
+    if !disagreements(&outcomes).is_empty() {
+        std::process::exit(1);
+    }
+
+    true
+}
+
+// Random numbers for generating inputs to compare strategies on. The seed is fixed, so a
+// generated input and the timings measured on it are the same from one run to the next.
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    pub fn new() -> Self {
+        Self(0x2545f4914f6cdd1d)
+    }
+
+    // A number in 0..n, close enough to uniform for test data
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+impl Default for Xorshift {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: [Strategy<[u64]>; 4] = [
+        Strategy {
+            part: 1,
+            name: "iterator",
+            run: |v| Some(v.iter().sum::<u64>().to_string()),
+        },
+        Strategy {
+            part: 1,
+            name: "loop",
+            run: |v| {
+                let mut sum = 0;
+                for n in v {
+                    sum += n;
+                }
+                Some(sum.to_string())
+            },
+        },
+        Strategy {
+            part: 2,
+            name: "max",
+            run: |v| Some(v.iter().max().unwrap().to_string()),
+        },
+        Strategy {
+            part: 2,
+            name: "last",
+            run: |v| Some(v.last().unwrap().to_string()),
+        },
+    ];
+
+    #[test]
+    fn test_compare() {
+        let outcomes = compare(&[1, 5, 3][..], &STRATEGIES);
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|o| o.answer.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["9", "9", "5", "3"]
+        );
+        assert_eq!(disagreements(&outcomes), vec![2]);
+
+        let outcomes = compare(&[1, 3, 5][..], &STRATEGIES);
+        assert!(disagreements(&outcomes).is_empty());
+    }
+
+    #[test]
+    fn test_report() {
+        let outcomes = compare(&[1, 5, 3][..], &STRATEGIES);
+        let report = report(&outcomes);
+        assert!(report.starts_with("Part1: ok\n  iterator  9  "));
+        assert!(report.contains("\n  loop      9  "));
+        assert!(report.contains("Part2: MISMATCH\n  max       5  "));
+    }
+
+    #[test]
+    fn test_inconclusive() {
+        let strategies = [
+            Strategy {
+                part: 1,
+                name: "max",
+                run: |v: &[u64]| Some(v.iter().max().unwrap().to_string()),
+            },
+            Strategy {
+                part: 1,
+                name: "sorted",
+                run: |v| v.is_sorted().then(|| v.last().unwrap().to_string()),
+            },
+        ];
+        let outcomes = compare(&[1, 5, 3][..], &strategies);
+        assert_eq!(outcomes[1].answer, None);
+        assert!(disagreements(&outcomes).is_empty());
+        assert!(report(&outcomes).contains("\n  sorted  inconclusive  "));
+    }
+
+    #[test]
+    fn test_xorshift() {
+        let numbers = |n| {
+            let mut random = Xorshift::new();
+            (0..1000).map(|_| random.below(n)).collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(10), numbers(10));
+        assert!(numbers(10).iter().all(|&n| n < 10));
+        assert_eq!(numbers(1000).iter().max(), Some(&999));
+    }
+}
@@ -1,7 +1,51 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // For inputs too big to hold both lists in memory, sort them on disk instead
+    if args.contains(&String::from("--stream")) {
+        let (left, right) = sort_lists_from_file("input/day01.txt")?;
+
+        println!(
+            "Part1 sum: {}",
+            part1_counts(left.counts()?, right.counts()?)?
+        );
+        println!(
+            "Part2 sum: {}",
+            part2_counts(left.counts()?, right.counts()?)?
+        );
+
+        return Ok(());
+    }
+
+    // Reports for comparing any number of columns, e.g. `--pairwise --metric squared`
+    if args.contains(&String::from("--pairwise")) || args.contains(&String::from("--breakdown")) {
+        let metric = match args.iter().position(|a| a == "--metric") {
+            Some(i) => args.get(i + 1).and_then(|name| Metric::from_name(name)),
+            None => Some(Metric::Absolute),
+        };
+        let Some(metric) = metric else {
+            eprintln!("--metric must be one of absolute, squared, rank or similarity");
+            std::process::exit(1);
+        };
+        let columns = parse_columns_from_file("input/day01.txt")?;
+
+        if args.contains(&String::from("--pairwise")) {
+            print!("{}", pairwise_report(&columns, metric));
+        }
+        if args.contains(&String::from("--breakdown")) {
+            print!("{}", breakdown_report(&columns, metric));
+        }
+
+        return Ok(());
+    }
+
     let (mut left_list, mut right_list) = parse_lists_from_file("input/day01.txt")?;
     left_list.sort();
     right_list.sort();
@@ -12,15 +56,25 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
-fn parse_lists_from_file(fname: &str) -> std::io::Result<(Vec<i32>, Vec<i32>)> {
+fn parse_lists_from_file(fname: &str) -> std::io::Result<(Vec<i64>, Vec<i64>)> {
+    let mut columns = parse_columns_from_file(fname)?.into_iter();
+    match (columns.next(), columns.next()) {
+        (Some(left_list), Some(right_list)) => Ok((left_list, right_list)),
+        _ => Err(Error::new(ErrorKind::InvalidData, "expected two columns")),
+    }
+}
+
+fn parse_columns_from_file(fname: &str) -> std::io::Result<Vec<Vec<i64>>> {
     let f = File::open(fname)?;
-    let reader = BufReader::new(f);
+    parse_columns(BufReader::new(f))
+}
 
+// Any number of whitespace separated columns, as long as every row has the same number
+fn parse_columns(reader: impl BufRead) -> std::io::Result<Vec<Vec<i64>>> {
     let lines = reader.lines();
 
-    let mut left_list = Vec::new();
-    let mut right_list = Vec::new();
-    for line in lines {
+    let mut columns: Vec<Vec<i64>> = Vec::new();
+    for (i, line) in lines.enumerate() {
         let Ok(line) = line else {
             continue;
         };
@@ -29,29 +83,440 @@ fn parse_lists_from_file(fname: &str) -> std::io::Result<(Vec<i32>, Vec<i32>)> {
         }
         let nums = line
             .split_whitespace()
-            .map(|num| num.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-        left_list.push(nums[0]);
-        right_list.push(nums[1]);
+            .map(|num| num.parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("line {}: {}", i + 1, e)))?;
+        if columns.is_empty() {
+            columns = vec![Vec::new(); nums.len()];
+        }
+        if nums.len() != columns.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "line {}: expected {} columns, found {}",
+                    i + 1,
+                    columns.len(),
+                    nums.len()
+                ),
+            ));
+        }
+        for (column, num) in columns.iter_mut().zip(nums) {
+            column.push(num);
+        }
+    }
+
+    Ok(columns)
+}
+
+// One row of exactly two columns, with errors worded like `parse_columns`' ones
+fn parse_line(line: &str, line_number: usize) -> std::io::Result<(i64, i64)> {
+    let nums = line
+        .split_whitespace()
+        .map(|num| num.parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("line {}: {}", line_number, e),
+            )
+        })?;
+    match nums[..] {
+        [left, right] => Ok((left, right)),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "line {}: expected 2 columns, found {}",
+                line_number,
+                nums.len()
+            ),
+        )),
+    }
+}
+
+// Values of each column sorted in memory at once. Anything beyond that is written out to
+// temporary files in sorted runs, which are merged back together afterwards.
+const RUN_LEN: usize = 1 << 20;
+
+// One column sorted in runs, all but the last of which live in temporary files. The files
+// are removed again when this is dropped.
+struct SortedRuns {
+    files: Vec<PathBuf>,
+    last: Vec<i64>,
+}
+
+type Run<'a> = Box<dyn Iterator<Item = std::io::Result<i64>> + 'a>;
+
+impl SortedRuns {
+    fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            last: Vec::new(),
+        }
+    }
+
+    fn spill(&mut self, values: &mut Vec<i64>) -> std::io::Result<()> {
+        // Tests sort several inputs at once, so the process ID alone isn't unique enough
+        static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "day01-{}-{}.run",
+            std::process::id(),
+            NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.files.push(path.clone());
+
+        values.sort_unstable();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for value in values.iter() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.flush()?;
+        values.clear();
+
+        Ok(())
     }
 
-    Ok((left_list, right_list))
+    fn finish(&mut self, mut values: Vec<i64>) {
+        values.sort_unstable();
+        self.last = values;
+    }
+
+    // Distinct values in ascending order, each with how many times it appears
+    fn counts(&self) -> std::io::Result<Counts<'_>> {
+        let mut runs = self
+            .files
+            .iter()
+            .map(|path| read_run(path))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        runs.push(Box::new(self.last.iter().copied().map(Ok)));
+
+        let mut counts = Counts {
+            runs,
+            heads: BinaryHeap::new(),
+        };
+        for i in 0..counts.runs.len() {
+            counts.advance(i)?;
+        }
+
+        Ok(counts)
+    }
+}
+
+impl Drop for SortedRuns {
+    fn drop(&mut self) {
+        for path in &self.files {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn read_run(path: &Path) -> std::io::Result<Run<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(Box::new(std::iter::from_fn(move || {
+        let mut bytes = [0; 8];
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => Some(Ok(i64::from_le_bytes(bytes))),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    })))
+}
+
+// A k-way merge of the runs, with the smallest value at the front of each run in the heap
+struct Counts<'a> {
+    runs: Vec<Run<'a>>,
+    heads: BinaryHeap<Reverse<(i64, usize)>>,
+}
+
+impl Counts<'_> {
+    fn advance(&mut self, run: usize) -> std::io::Result<()> {
+        if let Some(value) = self.runs[run].next().transpose()? {
+            self.heads.push(Reverse((value, run)));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Counts<'_> {
+    type Item = std::io::Result<(i64, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &Reverse((value, _)) = self.heads.peek()?;
+        let mut count = 0;
+        while let Some(&Reverse((next, run))) = self.heads.peek() {
+            if next != value {
+                break;
+            }
+            self.heads.pop();
+            count += 1;
+            if let Err(e) = self.advance(run) {
+                return Some(Err(e));
+            }
+        }
+
+        Some(Ok((value, count)))
+    }
 }
 
-fn part1(left_list: &[i32], right_list: &[i32]) -> i32 {
+fn sort_lists_from_file(fname: &str) -> std::io::Result<(SortedRuns, SortedRuns)> {
+    let f = File::open(fname)?;
+    sort_lists(BufReader::new(f), RUN_LEN)
+}
+
+// Only `run_len` values per column are held in memory at a time
+fn sort_lists(
+    mut reader: impl BufRead,
+    run_len: usize,
+) -> std::io::Result<(SortedRuns, SortedRuns)> {
+    let (mut left_runs, mut right_runs) = (SortedRuns::new(), SortedRuns::new());
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+
+    // Reuse one buffer rather than allocating a String for every line
+    let mut buffer = String::new();
+    let mut line_number = 0;
+    while reader.read_line(&mut buffer)? > 0 {
+        line_number += 1;
+        let line = buffer.trim();
+        if !line.is_empty() {
+            let (left_val, right_val) = parse_line(line, line_number)?;
+            left.push(left_val);
+            right.push(right_val);
+            if left.len() == run_len {
+                left_runs.spill(&mut left)?;
+                right_runs.spill(&mut right)?;
+            }
+        }
+        buffer.clear();
+    }
+    left_runs.finish(left);
+    right_runs.finish(right);
+
+    Ok((left_runs, right_runs))
+}
+
+fn part1(left_list: &[i64], right_list: &[i64]) -> i64 {
     left_list
         .iter()
         .zip(right_list)
         .fold(0, |acc, (&left, &right)| acc + (left - right).abs())
 }
 
-fn part2(left_list: &[i32], right_list: &[i32]) -> usize {
+fn part2(left_list: &[i64], right_list: &[i64]) -> i64 {
+    let mut right_counts = HashMap::new();
+    for &num in right_list {
+        *right_counts.entry(num).or_insert(0) += 1;
+    }
+
+    left_list
+        .iter()
+        .map(|num| num * right_counts.get(num).unwrap_or(&0))
+        .sum()
+}
+
+// Pair up the sorted lists by walking both sets of counts in order
+fn part1_counts(
+    mut left: impl Iterator<Item = std::io::Result<(i64, u64)>>,
+    mut right: impl Iterator<Item = std::io::Result<(i64, u64)>>,
+) -> std::io::Result<i64> {
     let mut sum = 0;
-    for num in left_list {
-        sum += *num as usize * right_list.iter().filter(|&num2| *num2 == *num).count();
+    let (mut l, mut r) = (left.next().transpose()?, right.next().transpose()?);
+    while let (Some((left_val, left_count)), Some((right_val, right_count))) = (l, r) {
+        let pairs = left_count.min(right_count);
+        sum += pairs as i64 * (left_val - right_val).abs();
+
+        l = if left_count == pairs {
+            left.next().transpose()?
+        } else {
+            Some((left_val, left_count - pairs))
+        };
+        r = if right_count == pairs {
+            right.next().transpose()?
+        } else {
+            Some((right_val, right_count - pairs))
+        };
+    }
+
+    Ok(sum)
+}
+
+// Both sets of counts are sorted, so matching values are found by merging them
+fn part2_counts(
+    mut left: impl Iterator<Item = std::io::Result<(i64, u64)>>,
+    mut right: impl Iterator<Item = std::io::Result<(i64, u64)>>,
+) -> std::io::Result<i64> {
+    let mut sum = 0;
+    let (mut l, mut r) = (left.next().transpose()?, right.next().transpose()?);
+    while let (Some((left_val, left_count)), Some((right_val, right_count))) = (l, r) {
+        if left_val <= right_val {
+            l = left.next().transpose()?;
+        }
+        if right_val <= left_val {
+            r = right.next().transpose()?;
+        }
+        if left_val == right_val {
+            sum += left_val * (left_count * right_count) as i64;
+        }
+    }
+
+    Ok(sum)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    // Distance between the nth smallest values, as in part 1
+    Absolute,
+    // Squared distance between the nth smallest values
+    Squared,
+    // How far apart each row's values are ranked within their own columns
+    Rank,
+    // Each value weighted by how often it appears in the other column, as in part 2
+    Similarity,
+}
+
+impl Metric {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "absolute" => Some(Metric::Absolute),
+            "squared" => Some(Metric::Squared),
+            "rank" => Some(Metric::Rank),
+            "similarity" => Some(Metric::Similarity),
+            _ => None,
+        }
+    }
+
+    // The values each row of a breakdown refers to
+    fn rows(&self, column: &[i64]) -> Vec<i64> {
+        match self {
+            Metric::Absolute | Metric::Squared => {
+                let mut sorted = column.to_vec();
+                sorted.sort();
+                sorted
+            }
+            Metric::Rank | Metric::Similarity => column.to_vec(),
+        }
     }
 
-    sum
+    // Score for each row when comparing `reference` against `other`
+    fn row_scores(&self, reference: &[i64], other: &[i64]) -> Vec<i64> {
+        match self {
+            Metric::Absolute => self
+                .rows(reference)
+                .iter()
+                .zip(self.rows(other))
+                .map(|(a, b)| (a - b).abs())
+                .collect(),
+            Metric::Squared => self
+                .rows(reference)
+                .iter()
+                .zip(self.rows(other))
+                .map(|(a, b)| (a - b) * (a - b))
+                .collect(),
+            Metric::Rank => ranks(reference)
+                .iter()
+                .zip(ranks(other))
+                .map(|(&a, b)| a.abs_diff(b) as i64)
+                .collect(),
+            Metric::Similarity => {
+                let mut counts = HashMap::new();
+                for &num in other {
+                    *counts.entry(num).or_insert(0) += 1;
+                }
+                reference
+                    .iter()
+                    .map(|num| num * counts.get(num).unwrap_or(&0))
+                    .collect()
+            }
+        }
+    }
+
+    fn total(&self, reference: &[i64], other: &[i64]) -> i64 {
+        self.row_scores(reference, other).iter().sum()
+    }
+}
+
+// Position of each value in its sorted column, ties broken by row order
+fn ranks(column: &[i64]) -> Vec<usize> {
+    let mut order = (0..column.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| column[i]);
+
+    let mut ranks = vec![0; column.len()];
+    for (rank, i) in order.into_iter().enumerate() {
+        ranks[i] = rank;
+    }
+
+    ranks
+}
+
+// Total score for every pair of columns, with rows as the reference column
+fn pairwise_report(columns: &[Vec<i64>], metric: Metric) -> String {
+    let cells = columns
+        .iter()
+        .map(|reference| {
+            columns
+                .iter()
+                .map(|other| {
+                    if std::ptr::eq(reference, other) {
+                        String::from("-")
+                    } else {
+                        metric.total(reference, other).to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let header = |i: usize| format!("col{}", i);
+    let width = cells
+        .iter()
+        .flatten()
+        .map(|c| c.len())
+        .chain((0..columns.len()).map(|i| header(i).len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut result = format!("{:>6}", "");
+    for j in 0..columns.len() {
+        result.push_str(&format!("  {:>width$}", header(j)));
+    }
+    result.push('\n');
+    for (i, row) in cells.iter().enumerate() {
+        result.push_str(&format!("{:>6}", header(i)));
+        for cell in row {
+            result.push_str(&format!("  {:>width$}", cell));
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+// Score of every row of the first column against each of the others, and the total
+fn breakdown_report(columns: &[Vec<i64>], metric: Metric) -> String {
+    let Some((reference, others)) = columns.split_first() else {
+        return String::new();
+    };
+    let rows = columns.iter().map(|c| metric.rows(c)).collect::<Vec<_>>();
+    let scores = others
+        .iter()
+        .map(|other| metric.row_scores(reference, other))
+        .collect::<Vec<_>>();
+
+    let mut result = String::new();
+    let mut total = 0;
+    for i in 0..reference.len() {
+        let values = rows.iter().map(|r| r[i].to_string()).collect::<Vec<_>>();
+        let row_scores = scores.iter().map(|s| s[i]).collect::<Vec<_>>();
+        let row_total = row_scores.iter().sum::<i64>();
+        total += row_total;
+        result.push_str(&format!(
+            "{}: {} -> {:?} = {}\n",
+            i,
+            values.join(" "),
+            row_scores,
+            row_total
+        ));
+    }
+    result.push_str(&format!("Total: {}\n", total));
+
+    result
 }
 
 #[cfg(test)]
@@ -75,4 +540,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_stream() -> std::io::Result<()> {
+        let (left, right) = sort_lists_from_file("../test_input/day01test.txt")?;
+        assert_eq!(part1_counts(left.counts()?, right.counts()?)?, 11);
+        assert_eq!(part2_counts(left.counts()?, right.counts()?)?, 31);
+
+        // Two values per run, so most of each column goes through temporary files
+        let f = File::open("../test_input/day01test.txt")?;
+        let (left, right) = sort_lists(BufReader::new(f), 2)?;
+        assert_eq!(left.files.len(), 3);
+        assert_eq!(
+            left.counts()?.collect::<std::io::Result<Vec<_>>>()?,
+            vec![(1, 1), (2, 1), (3, 3), (4, 1)]
+        );
+        assert_eq!(part1_counts(left.counts()?, right.counts()?)?, 11);
+        assert_eq!(part2_counts(left.counts()?, right.counts()?)?, 31);
+
+        let files = left.files.clone();
+        drop(left);
+        assert!(files.iter().all(|path| !path.exists()));
+
+        for (input, error) in [
+            ("3   4\n4   x\n", "line 2: invalid digit found in string"),
+            ("3   4\n\n4\n", "line 3: expected 2 columns, found 1"),
+        ] {
+            let Err(e) = sort_lists(input.as_bytes(), 2) else {
+                panic!("{:?} should not parse", input);
+            };
+            assert_eq!(e.kind(), ErrorKind::InvalidData);
+            assert_eq!(e.to_string(), error);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_overflow() -> std::io::Result<()> {
+        let input = "3000000000   0\n3000000000   3000000000\n0   3000000000\n";
+        let (left, right) = sort_lists(input.as_bytes(), 2)?;
+        assert_eq!(part1_counts(left.counts()?, right.counts()?)?, 0);
+        assert_eq!(
+            part2_counts(left.counts()?, right.counts()?)?,
+            12_000_000_000
+        );
+
+        let (left, right) = sort_lists("2000000000   0\n2000000000   0\n".as_bytes(), 2)?;
+        assert_eq!(
+            part1_counts(left.counts()?, right.counts()?)?,
+            4_000_000_000
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_metrics() -> std::io::Result<()> {
+        let columns = parse_columns_from_file("../test_input/day01test.txt")?;
+        assert_eq!(Metric::Absolute.total(&columns[0], &columns[1]), 11);
+        assert_eq!(Metric::Similarity.total(&columns[0], &columns[1]), 31);
+        // sorted pairs (1, 3), (2, 3), (3, 3), (3, 4), (3, 5), (4, 9)
+        assert_eq!(Metric::Squared.total(&columns[0], &columns[1]), 35);
+        assert_eq!(
+            Metric::Rank.row_scores(&[10, 20, 30], &[3, 1, 2]),
+            vec![2, 1, 1]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_columns() -> std::io::Result<()> {
+        let columns = parse_columns("3 4 1\n1 3 2\n2 2 3\n".as_bytes())?;
+        assert_eq!(columns, vec![vec![3, 1, 2], vec![4, 3, 2], vec![1, 2, 3]]);
+        assert!(parse_columns("3 4 1\n1 3\n".as_bytes()).is_err());
+
+        assert_eq!(
+            breakdown_report(&columns, Metric::Absolute),
+            "0: 1 2 1 -> [1, 0] = 1\n1: 2 3 2 -> [1, 0] = 1\n2: 3 4 3 -> [1, 0] = 1\nTotal: 3\n"
+        );
+        assert_eq!(
+            pairwise_report(&columns, Metric::Absolute),
+            "        col0  col1  col2\n  col0     -     3     0\n  col1     3     -     3\n  col2     0     3     -\n"
+        );
+
+        Ok(())
+    }
 }
@@ -1,16 +1,37 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let reports = parse_reports("input/day02.txt")?;
 
+    let rules = match Rules::from_args(&args) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if args.contains(&String::from("--annotate")) {
+        for report in &reports {
+            println!("{}", annotate_report(report, &rules));
+        }
+        return Ok(());
+    }
+
+    let part1_rules = Rules {
+        removable: 0,
+        ..rules
+    };
     let part1 = reports
         .iter()
-        .filter(|&report| check_report(report))
+        .filter(|&report| check_report(report, &part1_rules))
         .count();
     let part2 = reports
         .iter()
-        .filter(|&report| check_report_part2(report))
+        .filter(|&report| check_report(report, &rules))
         .count();
 
     println!("part1: {}", part1);
@@ -43,36 +64,263 @@ fn parse_reports(fname: &str) -> std::io::Result<Vec<Vec<i32>>> {
     Ok(reports)
 }
 
-fn check_report(report: &[i32]) -> bool {
-    let increasing = report[1] > report[0];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    DirectionChange,
+    StepTooSmall,
+    StepTooLarge,
+}
 
-    for nums in report.windows(2).collect::<Vec<&[i32]>>() {
-        match (increasing, nums[1] - nums[0]) {
-            (true, 1..=3) => continue,
-            (false, -3..=-1) => continue,
-            _ => return false,
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::DirectionChange => write!(f, "direction change"),
+            Violation::StepTooSmall => write!(f, "step too small"),
+            Violation::StepTooLarge => write!(f, "step too large"),
         }
     }
+}
+
+// Where a report first breaks the rules: `index` is the level that can't follow the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Diagnosis {
+    index: usize,
+    violation: Violation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Verdict {
+    Safe,
+    // Safe once the levels at these indices are removed
+    Dampened(Vec<usize>),
+    Unsafe(Diagnosis),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Monotonicity {
+    Increasing,
+    Decreasing,
+    Either,
+}
 
-    true
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rules {
+    min_step: i32,
+    max_step: i32,
+    monotonicity: Monotonicity,
+    // How many levels the Problem Dampener may remove
+    removable: usize,
 }
 
-fn check_report_part2(report: &[i32]) -> bool {
-    let mut report_vec = Vec::from(report);
-    if check_report(report) {
-        return true;
+const PART1_RULES: Rules = Rules {
+    min_step: 1,
+    max_step: 3,
+    monotonicity: Monotonicity::Either,
+    removable: 0,
+};
+
+const PART2_RULES: Rules = Rules {
+    removable: 1,
+    ..PART1_RULES
+};
+
+impl Rules {
+    // Part 2's rules, overridden by `--min-step`, `--max-step`, `--removable`,
+    // `--increasing` and `--decreasing`
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let value = |flag: &str| -> Result<Option<usize>, String> {
+            match args.iter().position(|a| a == flag) {
+                Some(i) => args
+                    .get(i + 1)
+                    .and_then(|v| v.parse().ok())
+                    .map(Some)
+                    .ok_or(format!("{} expects a non-negative number", flag)),
+                None => Ok(None),
+            }
+        };
+
+        let mut rules = PART2_RULES;
+        if let Some(min_step) = value("--min-step")? {
+            rules.min_step = min_step as i32;
+        }
+        if let Some(max_step) = value("--max-step")? {
+            rules.max_step = max_step as i32;
+        }
+        if let Some(removable) = value("--removable")? {
+            rules.removable = removable;
+        }
+        rules.monotonicity = match (
+            args.contains(&String::from("--increasing")),
+            args.contains(&String::from("--decreasing")),
+        ) {
+            (true, false) => Monotonicity::Increasing,
+            (false, true) => Monotonicity::Decreasing,
+            _ => Monotonicity::Either,
+        };
+
+        if rules.min_step > rules.max_step {
+            return Err(String::from("--min-step can't be larger than --max-step"));
+        }
+
+        Ok(rules)
+    }
+
+    // 1 for increasing, -1 for decreasing
+    fn directions(&self) -> &'static [i32] {
+        match self.monotonicity {
+            Monotonicity::Increasing => &[1],
+            Monotonicity::Decreasing => &[-1],
+            Monotonicity::Either => &[1, -1],
+        }
     }
 
-    for (i, &num) in report.iter().enumerate() {
-        report_vec.remove(i);
-        if check_report(&report_vec) {
-            return true;
+    fn allows(&self, direction: i32, from: i32, to: i32) -> bool {
+        (self.min_step..=self.max_step).contains(&((to - from) * direction))
+    }
+}
+
+fn diagnose_report(report: &[i32], rules: &Rules) -> Option<Diagnosis> {
+    // With either direction allowed, the first change decides which one the report follows
+    let direction = match rules.monotonicity {
+        Monotonicity::Increasing => 1,
+        Monotonicity::Decreasing => -1,
+        Monotonicity::Either => report
+            .windows(2)
+            .map(|nums| (nums[1] - nums[0]).signum())
+            .find(|&sign| sign != 0)
+            .unwrap_or(1),
+    };
+
+    for (i, nums) in report.windows(2).enumerate() {
+        let step = (nums[1] - nums[0]) * direction;
+        let violation = if step < 0 {
+            Violation::DirectionChange
+        } else if step < rules.min_step {
+            Violation::StepTooSmall
+        } else if step > rules.max_step {
+            Violation::StepTooLarge
         } else {
-            report_vec.insert(i, num);
+            continue;
+        };
+        return Some(Diagnosis {
+            index: i + 1,
+            violation,
+        });
+    }
+
+    None
+}
+
+// The fewest levels to remove to make the report safe, or None if that takes more than
+// `rules.removable`. Keeping level i after level j removes everything between them, so
+// only the last `removable + 1` levels need checking: O(n * k) per report.
+fn dampen(report: &[i32], rules: &Rules) -> Option<Vec<usize>> {
+    let n = report.len();
+    let k = rules.removable;
+    if n == 0 {
+        return Some(Vec::new());
+    }
+
+    let mut best: Option<Vec<usize>> = None;
+    for &direction in rules.directions() {
+        // Removals for the cheapest kept sequence ending at each level, and the level kept before it
+        let mut costs = vec![usize::MAX; n];
+        let mut previous = vec![None; n];
+        for i in 0..n {
+            if i <= k {
+                costs[i] = i;
+            }
+            for j in i.saturating_sub(k + 1)..i {
+                if costs[j] == usize::MAX || !rules.allows(direction, report[j], report[i]) {
+                    continue;
+                }
+                // On a tie keep the later level, so the earlier one is the one removed
+                let cost = costs[j] + i - j - 1;
+                if cost <= costs[i] {
+                    costs[i] = cost;
+                    previous[i] = Some(j);
+                }
+            }
+        }
+
+        let Some((cost, last)) = ((n - 1).saturating_sub(k)..n)
+            .filter(|&i| costs[i] != usize::MAX)
+            .map(|i| (costs[i] + n - 1 - i, i))
+            .min()
+        else {
+            continue;
+        };
+        if cost > k || best.as_ref().is_some_and(|b| b.len() <= cost) {
+            continue;
+        }
+
+        let mut kept = vec![false; n];
+        let mut current = Some(last);
+        while let Some(i) = current {
+            kept[i] = true;
+            current = previous[i];
         }
+        best = Some((0..n).filter(|&i| !kept[i]).collect());
+    }
+
+    best
+}
+
+// The fewest levels to remove to make the report safe, however many that is
+fn min_removals(report: &[i32], rules: &Rules) -> usize {
+    let rules = Rules {
+        removable: report.len(),
+        ..*rules
+    };
+    dampen(report, &rules).map_or(report.len(), |removed| removed.len())
+}
+
+fn classify_report(report: &[i32], rules: &Rules) -> Verdict {
+    let Some(diagnosis) = diagnose_report(report, rules) else {
+        return Verdict::Safe;
+    };
+
+    match dampen(report, rules) {
+        Some(removed) => Verdict::Dampened(removed),
+        None => Verdict::Unsafe(diagnosis),
     }
+}
+
+fn check_report(report: &[i32], rules: &Rules) -> bool {
+    dampen(report, rules).is_some()
+}
+
+fn annotate_report(report: &[i32], rules: &Rules) -> String {
+    let line = report
+        .iter()
+        .map(|num| num.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
 
-    false
+    match classify_report(report, rules) {
+        Verdict::Safe => format!("{}: safe", line),
+        Verdict::Dampened(removed) => {
+            let diagnosis = diagnose_report(report, rules).unwrap();
+            let levels = removed
+                .iter()
+                .map(|&i| format!("level {} ({})", i, report[i]))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{}: {} at index {}, safe without {}",
+                line, diagnosis.violation, diagnosis.index, levels
+            )
+        }
+        Verdict::Unsafe(diagnosis) => format!(
+            "{}: unsafe, {} at index {} ({} -> {}), needs {} removals",
+            line,
+            diagnosis.violation,
+            diagnosis.index,
+            report[diagnosis.index - 1],
+            report[diagnosis.index],
+            min_removals(report, rules)
+        ),
+    }
 }
 
 #[cfg(test)]
@@ -84,7 +332,7 @@ mod tests {
         let reports = parse_reports("../test_input/day02test.txt")?;
         let part1 = reports
             .iter()
-            .filter(|&report| check_report(report))
+            .filter(|&report| check_report(report, &PART1_RULES))
             .count();
         assert_eq!(part1, 2);
 
@@ -96,10 +344,85 @@ mod tests {
         let reports = parse_reports("../test_input/day02test.txt")?;
         let part2 = reports
             .iter()
-            .filter(|&report| check_report_part2(report))
+            .filter(|&report| check_report(report, &PART2_RULES))
             .count();
         assert_eq!(part2, 4);
 
         Ok(())
     }
+
+    #[test]
+    fn test_diagnostics() -> std::io::Result<()> {
+        let reports = parse_reports("../test_input/day02test.txt")?;
+        let verdicts = reports
+            .iter()
+            .map(|report| classify_report(report, &PART2_RULES))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verdicts,
+            vec![
+                Verdict::Safe,
+                Verdict::Unsafe(Diagnosis {
+                    index: 2,
+                    violation: Violation::StepTooLarge
+                }),
+                Verdict::Unsafe(Diagnosis {
+                    index: 3,
+                    violation: Violation::StepTooLarge
+                }),
+                Verdict::Dampened(vec![1]),
+                Verdict::Dampened(vec![2]),
+                Verdict::Safe,
+            ]
+        );
+        assert_eq!(
+            diagnose_report(&reports[3], &PART1_RULES),
+            Some(Diagnosis {
+                index: 2,
+                violation: Violation::DirectionChange
+            })
+        );
+        assert_eq!(
+            annotate_report(&reports[4], &PART2_RULES),
+            "8 6 4 4 1: step too small at index 3, safe without level 2 (4)"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules() -> std::io::Result<()> {
+        let reports = parse_reports("../test_input/day02test.txt")?;
+        let removals = reports
+            .iter()
+            .map(|report| min_removals(report, &PART1_RULES))
+            .collect::<Vec<_>>();
+        assert_eq!(removals, vec![0, 2, 2, 1, 1, 0]);
+
+        let rules = Rules {
+            removable: 2,
+            ..PART2_RULES
+        };
+        assert_eq!(
+            classify_report(&reports[1], &rules),
+            Verdict::Dampened(vec![0, 1])
+        );
+
+        let rules = Rules {
+            max_step: 5,
+            monotonicity: Monotonicity::Increasing,
+            ..PART1_RULES
+        };
+        assert!(check_report(&reports[1], &rules));
+        assert!(!check_report(&reports[0], &rules));
+        assert_eq!(
+            diagnose_report(&reports[0], &rules),
+            Some(Diagnosis {
+                index: 1,
+                violation: Violation::DirectionChange
+            })
+        );
+
+        Ok(())
+    }
 }
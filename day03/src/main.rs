@@ -1,43 +1,325 @@
-use regex::Regex;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufReader, Read};
 
 fn main() -> std::io::Result<()> {
-    let f = File::open("input/day03.txt")?;
-    let reader = BufReader::new(f);
+    let args = std::env::args().collect::<Vec<_>>();
 
-    let lines = reader.lines();
+    let input: Box<dyn Read> = if args.contains(&String::from("--stdin")) {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(File::open("input/day03.txt")?)
+    };
 
-    let re = Regex::new(r"mul\((?P<num0>\d{1,3}),(?P<num1>\d{1,3})\)|do\(\)|don't\(\)").unwrap();
-    let mut part1_sum = 0;
-    let mut part2_sum = 0;
-    let mut en = true;
+    if args.contains(&String::from("--trace")) {
+        let execution = run(input)?;
+        for token in &execution.executed {
+            println!("{:>8}: {:?}", token.offset, token.instruction);
+        }
+        println!("Part 1: {}", execution.part1);
+        println!("Part 2: {}", execution.part2);
+        return Ok(());
+    }
+
+    // Only the running totals are kept, so this works on dumps of any size
+    let mut interpreter = Interpreter::new();
+    scan(input, |token| {
+        interpreter.step(token.instruction);
+    })?;
+
+    println!("Part 1: {}", interpreter.part1);
+    println!("Part 2: {}", interpreter.part2);
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
 
-    for line in lines {
-        let Ok(line) = line else {
-            continue;
-        };
-        if line.is_empty() {
-            continue;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Token {
+    // Byte offset into the memory dump
+    offset: usize,
+    instruction: Instruction,
+}
+
+// Everything that starts an instruction, up to its arguments
+const KEYWORDS: [&[u8]; 3] = [b"mul(", b"do()", b"don't()"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // Bytes matched so far of one or more keywords
+    Keyword(usize),
+    // Inside `mul(`, reading the argument at `index`
+    Mul {
+        nums: [i64; 2],
+        index: usize,
+        digits: usize,
+    },
+}
+
+// Recognises instructions one byte at a time, so the memory dump never has to be held
+// in memory and instructions can straddle line breaks
+struct Scanner {
+    state: State,
+    keyword: [u8; 7],
+    // Where the instruction being matched started
+    start: usize,
+    offset: usize,
+}
+
+impl Scanner {
+    fn new() -> Self {
+        Self {
+            state: State::Keyword(0),
+            keyword: [0; 7],
+            start: 0,
+            offset: 0,
         }
+    }
 
-        for cap in re.captures_iter(&line) {
-            match cap.get(0).unwrap().as_str() {
-                "do()" => en = true,
-                "don't()" => en = false,
-                _ => {
-                    let num0 = cap["num0"].parse::<i32>().unwrap();
-                    let num1 = cap["num1"].parse::<i32>().unwrap();
-                    part1_sum += num0 * num1;
-                    if en {
-                        part2_sum += num0 * num1;
+    fn push(&mut self, byte: u8) -> Option<Token> {
+        // The dump is one continuous stream that happens to be wrapped over several lines
+        if byte == b'\n' || byte == b'\r' {
+            self.offset += 1;
+            return None;
+        }
+        let resumed = self.state != State::Keyword(0);
+        let mut token = self.advance(byte);
+        // Whatever was matched so far is garbage, but this byte may start something new
+        if token.is_none() && resumed && self.state == State::Keyword(0) {
+            token = self.advance(byte);
+        }
+        self.offset += 1;
+
+        token
+    }
+
+    fn advance(&mut self, byte: u8) -> Option<Token> {
+        match self.state {
+            State::Keyword(len) => {
+                self.keyword[len] = byte;
+                let matched = &self.keyword[..=len];
+                let instruction = match matched {
+                    b"mul(" => {
+                        self.state = State::Mul {
+                            nums: [0; 2],
+                            index: 0,
+                            digits: 0,
+                        };
+                        return None;
                     }
-                }
+                    b"do()" => Instruction::Do,
+                    b"don't()" => Instruction::Dont,
+                    _ => {
+                        self.state = if KEYWORDS.iter().any(|k| k.starts_with(matched)) {
+                            if len == 0 {
+                                self.start = self.offset;
+                            }
+                            State::Keyword(len + 1)
+                        } else {
+                            State::Keyword(0)
+                        };
+                        return None;
+                    }
+                };
+                self.emit(instruction)
             }
+            State::Mul {
+                mut nums,
+                index,
+                digits,
+            } => match byte {
+                b'0'..=b'9' if digits < 3 => {
+                    nums[index] = nums[index] * 10 + (byte - b'0') as i64;
+                    self.state = State::Mul {
+                        nums,
+                        index,
+                        digits: digits + 1,
+                    };
+                    None
+                }
+                b',' if index == 0 && digits > 0 => {
+                    self.state = State::Mul {
+                        nums,
+                        index: 1,
+                        digits: 0,
+                    };
+                    None
+                }
+                b')' if index == 1 && digits > 0 => self.emit(Instruction::Mul(nums[0], nums[1])),
+                _ => {
+                    self.state = State::Keyword(0);
+                    None
+                }
+            },
+        }
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> Option<Token> {
+        self.state = State::Keyword(0);
+        Some(Token {
+            offset: self.start,
+            instruction,
+        })
+    }
+}
+
+// Feed the whole stream through the scanner, handing each instruction to `f` as it's found
+fn scan(reader: impl Read, mut f: impl FnMut(Token)) -> std::io::Result<()> {
+    let mut scanner = Scanner::new();
+    for byte in BufReader::new(reader).bytes() {
+        if let Some(token) = scanner.push(byte?) {
+            f(token);
         }
     }
-    println!("Part 1: {}", part1_sum);
-    println!("Part 2: {}", part2_sum);
 
     Ok(())
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Execution {
+    // Every mul, whether enabled or not
+    part1: i64,
+    // Only the enabled muls
+    part2: i64,
+    // Instructions that took effect, i.e. everything except disabled muls
+    executed: Vec<Token>,
+}
+
+struct Interpreter {
+    enabled: bool,
+    part1: i64,
+    part2: i64,
+}
+
+impl Interpreter {
+    fn new() -> Self {
+        Self {
+            enabled: true,
+            part1: 0,
+            part2: 0,
+        }
+    }
+
+    // Returns whether the instruction took effect
+    fn step(&mut self, instruction: Instruction) -> bool {
+        match instruction {
+            Instruction::Mul(num0, num1) => {
+                self.part1 += num0 * num1;
+                if !self.enabled {
+                    return false;
+                }
+                self.part2 += num0 * num1;
+            }
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = false,
+        }
+
+        true
+    }
+}
+
+fn run(reader: impl Read) -> std::io::Result<Execution> {
+    let mut interpreter = Interpreter::new();
+    let mut executed = Vec::new();
+    scan(reader, |token| {
+        if interpreter.step(token.instruction) {
+            executed.push(token);
+        }
+    })?;
+
+    Ok(Execution {
+        part1: interpreter.part1,
+        part2: interpreter.part2,
+        executed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenize(memory: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        scan(memory.as_bytes(), |token| tokens.push(token)).unwrap();
+        tokens
+    }
+
+    #[test]
+    fn test_part1() -> std::io::Result<()> {
+        let execution = run(File::open("../test_input/day03test.txt")?)?;
+        assert_eq!(execution.part1, 161);
+        assert_eq!(execution.part2, 161);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> std::io::Result<()> {
+        let execution = run(File::open("../test_input/day03part2test.txt")?)?;
+        assert_eq!(execution.part1, 161);
+        assert_eq!(execution.part2, 48);
+        assert_eq!(
+            execution.executed,
+            vec![
+                Token {
+                    offset: 1,
+                    instruction: Instruction::Mul(2, 4)
+                },
+                Token {
+                    offset: 20,
+                    instruction: Instruction::Dont
+                },
+                Token {
+                    offset: 59,
+                    instruction: Instruction::Do
+                },
+                Token {
+                    offset: 64,
+                    instruction: Instruction::Mul(8, 5)
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("mul(4*mul(6,9!?(12,34)do()don't()mul(1234,1)");
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| token.instruction)
+                .collect::<Vec<_>>(),
+            vec![Instruction::Do, Instruction::Dont]
+        );
+
+        let tokens = tokenize("mmul(2,3)dodo()do(don't()\nmul(12,\n34)\n\nmul(1,1");
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    offset: 1,
+                    instruction: Instruction::Mul(2, 3)
+                },
+                Token {
+                    offset: 11,
+                    instruction: Instruction::Do
+                },
+                Token {
+                    offset: 18,
+                    instruction: Instruction::Dont
+                },
+                Token {
+                    offset: 26,
+                    instruction: Instruction::Mul(12, 34)
+                },
+            ]
+        );
+    }
+}
@@ -1,8 +1,71 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let xword = parse_xword("input/day04.txt")?;
+    // Show which letters were used rather than listing the matches
+    let highlight = args.contains(&String::from("--highlight"));
+
+    // Search for any words, e.g. `--words XMAS,SANTA --wrap`
+    if let Some(i) = args.iter().position(|a| a == "--words") {
+        let Some(words) = args.get(i + 1) else {
+            eprintln!("--words expects a comma separated list of words");
+            std::process::exit(1);
+        };
+        let words = words.split(',').collect::<Vec<_>>();
+        let wrap = args.contains(&String::from("--wrap"));
+        let matches = find_words(&xword, &words, wrap);
+        if highlight {
+            print!("{}", render(&xword, &word_cells(&xword, &words, &matches)));
+            return Ok(());
+        }
+        for m in matches {
+            println!(
+                "{} at ({}, {}) going {}",
+                words[m.word],
+                m.x,
+                m.y,
+                direction_name(m.direction)
+            );
+        }
+        return Ok(());
+    }
+
+    // Match a stencil with rows separated by `/`, e.g. `--stencil M.S/.A./M.S`
+    if let Some(i) = args.iter().position(|a| a == "--stencil") {
+        let Some(text) = args.get(i + 1) else {
+            eprintln!("--stencil expects rows separated by /");
+            std::process::exit(1);
+        };
+        let stencil = Stencil::parse(&text.replace('/', "\n"));
+        let matches = find_stencil(&xword, &stencil);
+        if highlight {
+            print!("{}", render(&xword, &stencil_cells(&stencil, &matches)));
+            return Ok(());
+        }
+        for m in &matches {
+            println!("({}, {}) as variant {}", m.x, m.y, m.variant);
+        }
+        println!("{} matches", matches.len());
+        return Ok(());
+    }
+
+    if highlight {
+        let matches = find_words(&xword, &["XMAS"], false);
+        println!("XMAS:");
+        print!(
+            "{}",
+            render(&xword, &word_cells(&xword, &["XMAS"], &matches))
+        );
+
+        let stencil = Stencil::parse(X_MAS);
+        let matches = find_stencil(&xword, &stencil);
+        println!("X-MAS:");
+        print!("{}", render(&xword, &stencil_cells(&stencil, &matches)));
+        return Ok(());
+    }
 
     println!("Part1: {}", part1(&xword));
     println!("Part2: {}", part2(&xword));
@@ -32,30 +95,102 @@ fn parse_xword(file_name: &str) -> std::io::Result<Vec<Vec<char>>> {
     Ok(xword)
 }
 
-fn part1(xword: &Vec<Vec<char>>) -> usize {
-    let mut result = 0;
-    for (i, line) in xword.iter().enumerate() {
-        result += count_xmas(&String::from_iter(line.clone().into_iter()));
-        result += count_xmas(&diag_string(0, i, true, &xword));
-        result += count_xmas(&diag_string(0, i, false, &xword));
-    }
-    for j in 1..xword[0].len() {
-        result += count_xmas(&diag_string(j, 0, true, &xword));
-        result += count_xmas(&diag_string(j, xword.len() - 1, false, &xword));
-    }
-    for i in 0..xword[0].len() {
-        result += count_xmas(&vertical_string(i, &xword));
+fn part1(xword: &[Vec<char>]) -> usize {
+    find_words(xword, &["XMAS"], false).len()
+}
+
+const X_MAS: &str = "M.S
+.A.
+M.S";
+
+fn part2(xword: &[Vec<char>]) -> usize {
+    find_stencil(xword, &Stencil::parse(X_MAS)).len()
+}
+
+// Unit steps as (dx, dy), clockwise starting east, with y growing downwards
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+fn direction_name(direction: (i32, i32)) -> &'static str {
+    match direction {
+        (1, 0) => "E",
+        (1, 1) => "SE",
+        (0, 1) => "S",
+        (-1, 1) => "SW",
+        (-1, 0) => "W",
+        (-1, -1) => "NW",
+        (0, -1) => "N",
+        (1, -1) => "NE",
+        _ => "?",
     }
+}
 
-    result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Match {
+    // Index into the list of words searched for
+    word: usize,
+    // Cell of the first letter
+    x: usize,
+    y: usize,
+    direction: (i32, i32),
 }
 
-fn part2(xword: &Vec<Vec<char>>) -> usize {
-    let mut result = 0;
-    for i in 1..(xword[0].len() - 1) {
-        for j in 1..(xword.len() - 1) {
-            if xword[j][i] == 'A' && check_for_x(i, j, &xword) {
-                result += 1;
+// Every occurrence of every word in all eight directions. With `wrap`, words may run off
+// one edge of the grid and continue from the opposite one.
+fn find_words(xword: &[Vec<char>], words: &[&str], wrap: bool) -> Vec<Match> {
+    let height = xword.len() as i64;
+    let width = xword.first().map_or(0, |line| line.len()) as i64;
+    let words = words
+        .iter()
+        .map(|word| word.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let letter_at = |x: i64, y: i64| -> Option<char> {
+        if wrap {
+            Some(xword[y.rem_euclid(height) as usize][x.rem_euclid(width) as usize])
+        } else if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some(xword[y as usize][x as usize])
+        } else {
+            None
+        }
+    };
+
+    let mut result = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            for (word, letters) in words.iter().enumerate() {
+                if letters.first() != Some(&xword[y as usize][x as usize]) {
+                    continue;
+                }
+                // A single letter reads the same every way, so only report it once
+                let directions = if letters.len() == 1 {
+                    &DIRECTIONS[..1]
+                } else {
+                    &DIRECTIONS[..]
+                };
+                for &direction in directions {
+                    let found = letters.iter().enumerate().all(|(i, &letter)| {
+                        let i = i as i64;
+                        letter_at(x + direction.0 as i64 * i, y + direction.1 as i64 * i)
+                            == Some(letter)
+                    });
+                    if found {
+                        result.push(Match {
+                            word,
+                            x: x as usize,
+                            y: y as usize,
+                            direction,
+                        });
+                    }
+                }
             }
         }
     }
@@ -63,50 +198,170 @@ fn part2(xword: &Vec<Vec<char>>) -> usize {
     result
 }
 
-fn check_for_x(x: usize, y: usize, xword: &Vec<Vec<char>>) -> bool {
-    if x == 0 || x == xword[0].len() - 1 {
-        return false;
+// A small 2D pattern where `.` matches any letter
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stencil {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+impl Stencil {
+    fn parse(text: &str) -> Self {
+        let cells = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // Pad ragged rows with wildcards so the stencil is rectangular
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        let cells = cells
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, None);
+                row
+            })
+            .collect();
+
+        Self { cells }
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
     }
-    if y == 0 || y == xword.len() - 1 {
-        return false;
+
+    fn width(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
     }
-    match (xword[y - 1][x - 1], xword[y + 1][x + 1]) {
-        ('M', 'S') => {}
-        ('S', 'M') => {}
-        _ => return false,
+
+    // A quarter turn clockwise
+    fn rotate(&self) -> Self {
+        let cells = (0..self.width())
+            .map(|x| (0..self.height()).rev().map(|y| self.cells[y][x]).collect())
+            .collect();
+
+        Self { cells }
+    }
+
+    // Mirrored left to right
+    fn reflect(&self) -> Self {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+
+        Self { cells }
     }
-    match (xword[y + 1][x - 1], xword[y - 1][x + 1]) {
-        ('M', 'S') => {}
-        ('S', 'M') => {}
-        _ => return false,
+
+    // All distinct rotations and reflections, starting with the stencil itself
+    fn variants(&self) -> Vec<Self> {
+        let mut variants: Vec<Self> = Vec::new();
+        for mut variant in [self.clone(), self.reflect()] {
+            for _ in 0..4 {
+                if !variants.contains(&variant) {
+                    variants.push(variant.clone());
+                }
+                variant = variant.rotate();
+            }
+        }
+
+        variants
     }
 
-    true
+    fn matches_at(&self, xword: &[Vec<char>], x: usize, y: usize) -> bool {
+        self.cells.iter().enumerate().all(|(j, row)| {
+            row.iter().enumerate().all(|(i, cell)| match cell {
+                Some(c) => xword
+                    .get(y + j)
+                    .and_then(|line| line.get(x + i))
+                    .is_some_and(|letter| letter == c),
+                None => y + j < xword.len() && x + i < xword[y + j].len(),
+            })
+        })
+    }
 }
 
-fn vertical_string(x: usize, xword: &Vec<Vec<char>>) -> String {
-    xword.iter().fold(String::new(), |mut acc, line| {
-        acc.push(line[x]);
-        acc
-    })
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StencilMatch {
+    // Top left corner of the stencil
+    x: usize,
+    y: usize,
+    // Index into the stencil's variants
+    variant: usize,
 }
 
-fn diag_string(x: usize, y: usize, descend: bool, xword: &Vec<Vec<char>>) -> String {
-    let mut result = String::new();
-    if descend {
-        for (i, j) in (x..xword[0].len()).zip(y..xword.len()) {
-            result.push(xword[j][i]);
-        }
-    } else {
-        for (i, j) in (x..xword[0].len()).zip((0..=y).rev()) {
-            result.push(xword[j][i]);
+// Every place any rotation or reflection of the stencil fits the grid
+fn find_stencil(xword: &[Vec<char>], stencil: &Stencil) -> Vec<StencilMatch> {
+    let variants = stencil.variants();
+
+    let mut result = Vec::new();
+    for y in 0..xword.len() {
+        for x in 0..xword[y].len() {
+            for (variant, v) in variants.iter().enumerate() {
+                if v.matches_at(xword, x, y) {
+                    result.push(StencilMatch { x, y, variant });
+                }
+            }
         }
     }
+
     result
 }
 
-fn count_xmas(word: &str) -> usize {
-    word.match_indices("XMAS").count() + word.match_indices("SAMX").count()
+// Cells covered by any of the word matches
+fn word_cells(xword: &[Vec<char>], words: &[&str], matches: &[Match]) -> HashSet<(usize, usize)> {
+    let height = xword.len() as i64;
+    let width = xword.first().map_or(0, |line| line.len()) as i64;
+
+    let mut cells = HashSet::new();
+    for m in matches {
+        for i in 0..words[m.word].chars().count() as i64 {
+            let x = m.x as i64 + m.direction.0 as i64 * i;
+            let y = m.y as i64 + m.direction.1 as i64 * i;
+            // Wrapped matches continue on the opposite edge
+            cells.insert((x.rem_euclid(width) as usize, y.rem_euclid(height) as usize));
+        }
+    }
+
+    cells
+}
+
+// Cells covered by the non-wildcard letters of any of the stencil matches
+fn stencil_cells(stencil: &Stencil, matches: &[StencilMatch]) -> HashSet<(usize, usize)> {
+    let variants = stencil.variants();
+
+    let mut cells = HashSet::new();
+    for m in matches {
+        for (j, row) in variants[m.variant].cells.iter().enumerate() {
+            for (i, cell) in row.iter().enumerate() {
+                if cell.is_some() {
+                    cells.insert((m.x + i, m.y + j));
+                }
+            }
+        }
+    }
+
+    cells
+}
+
+// The grid with the given cells highlighted and everything else dimmed
+fn render(xword: &[Vec<char>], cells: &HashSet<(usize, usize)>) -> String {
+    let mut result = String::new();
+    for (y, line) in xword.iter().enumerate() {
+        for (x, c) in line.iter().enumerate() {
+            if cells.contains(&(x, y)) {
+                result.push_str(&format!("\x1b[1;33m{}\x1b[0m", c));
+            } else {
+                result.push_str(&format!("\x1b[2m{}\x1b[0m", c));
+            }
+        }
+        result.push('\n');
+    }
+
+    result
 }
 
 #[cfg(test)]
@@ -130,4 +385,86 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_find_words() {
+        let xword = ["ABC", "DEF", "GHI"]
+            .iter()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let matches = find_words(&xword, &["AEI", "FD", "CGE"], false);
+        assert_eq!(
+            matches,
+            vec![Match {
+                word: 0,
+                x: 0,
+                y: 0,
+                direction: (1, 1)
+            }]
+        );
+
+        let matches = find_words(&xword, &["FD", "CGE", "BH"], true);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.word, m.x, m.y, direction_name(m.direction)))
+                .collect::<Vec<_>>(),
+            vec![(2, 1, 0, "N"), (1, 2, 0, "NE"), (0, 2, 1, "E")]
+        );
+
+        let matches = find_words(&xword, &["E", "Z"], true);
+        assert_eq!(
+            matches,
+            vec![Match {
+                word: 0,
+                x: 1,
+                y: 1,
+                direction: DIRECTIONS[0]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stencil() {
+        let stencil = Stencil::parse(X_MAS);
+        assert_eq!(stencil.variants().len(), 4);
+        assert_eq!(stencil.rotate(), Stencil::parse("M.M\n.A.\nS.S"));
+
+        // An L shape has all eight orientations
+        let stencil = Stencil::parse("AB\nC.");
+        assert_eq!(stencil.variants().len(), 8);
+
+        let xword = ["CAB", "xCA", "xxB"]
+            .iter()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let matches = find_stencil(&xword, &stencil);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.x, m.y, m.variant))
+                .collect::<Vec<_>>(),
+            vec![(1, 0, 0), (1, 0, 5), (1, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let xword = ["XMAS", "AMSX"]
+            .iter()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let matches = find_words(&xword, &["XMAS"], true);
+        let cells = word_cells(&xword, &["XMAS"], &matches);
+        assert_eq!(cells.len(), 4);
+
+        let stencil = Stencil::parse("M\nA");
+        let cells = stencil_cells(&stencil, &find_stencil(&xword, &stencil));
+        assert_eq!(cells, HashSet::from([(1, 0), (2, 0), (0, 1), (1, 1)]));
+        assert_eq!(
+            render(&xword[1..], &HashSet::from([(1, 0)])),
+            "\x1b[2mA\x1b[0m\x1b[1;33mM\x1b[0m\x1b[2mS\x1b[0m\x1b[2mX\x1b[0m\n"
+        );
+    }
 }
@@ -1,16 +1,58 @@
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Error, ErrorKind};
 
 type Rules = HashSet<(usize, usize)>;
 type Pages = Vec<Vec<usize>>;
 
+// Ways of validating updates, run against each other with `--compare` or `--bench`
+const STRATEGIES: [compare::Strategy<(Rules, Pages)>; 2] = [
+    compare::Strategy {
+        part: 1,
+        name: "pairwise",
+        run: |(rules, pages)| {
+            Some(
+                pages
+                    .iter()
+                    .filter(|p| is_valid_pairwise(p, rules))
+                    .map(|p| p[p.len() / 2])
+                    .sum::<usize>()
+                    .to_string(),
+            )
+        },
+    },
+    compare::Strategy {
+        part: 1,
+        name: "rule index",
+        run: |(rules, pages)| Some(part1(pages, rules).to_string()),
+    },
+];
+
 fn main() -> std::io::Result<()> {
-    let (rules, pages) = parse_input("input/day05.txt")?;
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // Compare the strategies on a generated input far bigger than the real one
+    if args.contains(&String::from("--bench")) {
+        let input = generate_input(1000, 5000, 200, 200);
+        print!(
+            "{}",
+            compare::report(&compare::compare(&input, &STRATEGIES))
+        );
+        return Ok(());
+    }
+
+    let input = parse_input("input/day05.txt")?;
+    if compare::start(&input, &STRATEGIES, &args) {
+        return Ok(());
+    }
+    let (rules, pages) = input;
 
     let part1 = part1(&pages, &rules);
-    let part2 = part2(&pages, &rules);
+    let part2 =
+        part2(&pages, &rules).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
     println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
@@ -25,7 +67,7 @@ fn parse_input(file_name: &str) -> std::io::Result<(Rules, Pages)> {
     let mut pages: Pages = Vec::new();
 
     let mut buffer = String::new();
-    while let Ok(_) = reader.read_line(&mut buffer) {
+    while reader.read_line(&mut buffer).is_ok() {
         let line = buffer.trim();
         if line.is_empty() {
             break;
@@ -39,7 +81,7 @@ fn parse_input(file_name: &str) -> std::io::Result<(Rules, Pages)> {
         buffer.clear();
     }
 
-    while let Ok(_) = reader.read_line(&mut buffer) {
+    while reader.read_line(&mut buffer).is_ok() {
         let line = buffer.trim();
         if line.is_empty() {
             break;
@@ -52,43 +94,104 @@ fn parse_input(file_name: &str) -> std::io::Result<(Rules, Pages)> {
 }
 
 fn part1(pages: &Pages, rules: &Rules) -> usize {
+    let mut index = RuleIndex::new(rules);
     pages
         .iter()
-        .filter(|p| is_valid(&p, &rules))
+        .filter(|p| index.is_valid(p))
         .map(|p| p[p.len() / 2])
         .sum()
 }
 
-fn part2(pages: &Pages, rules: &Rules) -> usize {
-    let part2_pages: Vec<Vec<usize>> = pages
-        .clone()
-        .into_iter()
-        .filter(|p| !is_valid(p, &rules))
-        .collect();
-    let mut fixed_pages = Vec::new();
-    for p in part2_pages {
-        let mut fixed = p.clone();
-        while !is_valid(&fixed, &rules) {
-            fix_pages(&mut fixed, &rules);
-        }
-        fixed_pages.push(fixed);
+fn part2(pages: &Pages, rules: &Rules) -> Result<usize, Cycle> {
+    let mut index = RuleIndex::new(rules);
+    let mut result = 0;
+    for p in pages.iter().filter(|p| !index.is_valid(p)) {
+        let fixed = sort_pages(p, rules)?;
+        result += fixed[fixed.len() / 2];
     }
 
-    fixed_pages.iter().map(|p| p[p.len() / 2]).sum()
+    Ok(result)
+}
+
+// Pages whose rules contradict each other, each one required to come before the next
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cycle(Vec<usize>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ordering rules form a cycle: ")?;
+        for page in &self.0 {
+            write!(f, "{} -> ", page)?;
+        }
+        write!(f, "{}", self.0[0])
+    }
 }
 
-fn fix_pages(pages: &mut Vec<usize>, rules: &Rules) {
-    for combination in pages.clone().iter().combinations(2) {
-        if rules.contains(&(*combination[1], *combination[0])) {
-            // Need to swap these
-            let pos0 = pages.iter().position(|p| p == combination[0]).unwrap();
-            let pos1 = pages.iter().position(|p| p == combination[1]).unwrap();
-            pages.swap(pos0, pos1);
+// Topologically sort an update using only the rules between its own pages. Whenever
+// several pages could come next, the one earliest in the original update goes first.
+fn sort_pages(pages: &[usize], rules: &Rules) -> Result<Vec<usize>, Cycle> {
+    let mut successors: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut predecessors: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(before, after) in rules {
+        if pages.contains(&before) && pages.contains(&after) {
+            successors.entry(before).or_default().push(after);
+            predecessors.entry(after).or_default().push(before);
+        }
+    }
+
+    let mut in_degree = pages
+        .iter()
+        .map(|p| (*p, predecessors.get(p).map_or(0, |v| v.len())))
+        .collect::<HashMap<_, _>>();
+    let position = pages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (*p, i))
+        .collect::<HashMap<_, _>>();
+    let mut ready = pages
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| in_degree[p] == 0)
+        .map(|(i, _)| Reverse(i))
+        .collect::<BinaryHeap<_>>();
+
+    let mut sorted = Vec::new();
+    while let Some(Reverse(i)) = ready.pop() {
+        let page = pages[i];
+        sorted.push(page);
+        for after in successors.get(&page).into_iter().flatten() {
+            let degree = in_degree.get_mut(after).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push(Reverse(position[after]));
+            }
         }
     }
+
+    if sorted.len() == pages.len() {
+        return Ok(sorted);
+    }
+
+    // Every page left over still has a predecessor that's left over, so walking
+    // backwards from any of them has to come round in a loop eventually
+    let mut page = *pages.iter().find(|p| in_degree[p] > 0).unwrap();
+    let mut path = Vec::new();
+    while !path.contains(&page) {
+        path.push(page);
+        page = *predecessors[&page]
+            .iter()
+            .find(|p| in_degree[p] > 0)
+            .unwrap();
+    }
+    let start = path.iter().position(|&p| p == page).unwrap();
+    let mut cycle = path.split_off(start);
+    cycle.reverse();
+
+    Err(Cycle(cycle))
 }
 
-fn is_valid(pages: &[usize], rules: &Rules) -> bool {
+// Checks every pair of pages, which is quadratic in the length of the update
+fn is_valid_pairwise(pages: &[usize], rules: &Rules) -> bool {
     let mut combinations = HashSet::new();
     for combination in pages.iter().combinations(2) {
         combinations.insert(combination);
@@ -99,6 +202,103 @@ fn is_valid(pages: &[usize], rules: &Rules) -> bool {
         .all(|comb| !rules.contains(&(*comb[1], *comb[0])))
 }
 
+// For each page that appears in a rule, the pages that have to come after it. Pages are
+// renumbered densely, so memory is linear in the number of rules whatever the page IDs are.
+struct RuleIndex {
+    ids: HashMap<usize, usize>,
+    successors: Vec<Vec<usize>>,
+    // The update each page was last seen in, so nothing is reallocated between updates
+    seen: Vec<usize>,
+    update: usize,
+}
+
+impl RuleIndex {
+    fn new(rules: &Rules) -> Self {
+        let mut ids = HashMap::new();
+        let mut successors = Vec::new();
+        let mut id = |page| {
+            *ids.entry(page).or_insert_with(|| {
+                successors.push(Vec::new());
+                successors.len() - 1
+            })
+        };
+        let edges = rules
+            .iter()
+            .map(|&(before, after)| (id(before), id(after)))
+            .collect::<Vec<_>>();
+        for (before, after) in edges {
+            successors[before].push(after);
+        }
+
+        Self {
+            seen: vec![0; ids.len()],
+            ids,
+            successors,
+            update: 0,
+        }
+    }
+
+    // One pass over the update, checking each page's successors against the pages already
+    // seen: O(n + the number of rules for the update's pages)
+    fn is_valid(&mut self, pages: &[usize]) -> bool {
+        self.update += 1;
+        for page in pages {
+            // Pages without rules can't break any
+            let Some(&id) = self.ids.get(page) else {
+                continue;
+            };
+            if self.successors[id]
+                .iter()
+                .any(|&after| self.seen[after] == self.update)
+            {
+                return false;
+            }
+            self.seen[id] = self.update;
+        }
+
+        true
+    }
+}
+
+// Rules consistent with a shuffled order of `pages` pages, and updates drawn from them of
+// which roughly half are correctly ordered
+fn generate_input(pages: usize, rules: usize, updates: usize, update_len: usize) -> (Rules, Pages) {
+    let mut random = compare::Xorshift::new();
+
+    let mut order = (0..pages).collect::<Vec<_>>();
+    for i in (1..pages).rev() {
+        order.swap(i, random.below(i + 1));
+    }
+    let mut rank = vec![0; pages];
+    for (i, &page) in order.iter().enumerate() {
+        rank[page] = i;
+    }
+
+    let mut generated_rules = Rules::new();
+    while generated_rules.len() < rules {
+        let (a, b) = (random.below(pages), random.below(pages));
+        if rank[a] < rank[b] {
+            generated_rules.insert((a, b));
+        }
+    }
+
+    let mut generated_updates = Vec::new();
+    for i in 0..updates {
+        let mut update = order.clone();
+        for j in 0..update_len {
+            let k = j + random.below(pages - j);
+            update.swap(j, k);
+        }
+        update.truncate(update_len);
+        if i % 2 == 0 {
+            update.sort_by_key(|&page| rank[page]);
+        }
+        generated_updates.push(update);
+    }
+
+    (generated_rules, generated_updates)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,7 +316,40 @@ mod tests {
     fn test_part2() -> std::io::Result<()> {
         let (rules, pages) = parse_input("../test_input/day05test.txt")?;
         let part2 = part2(&pages, &rules);
-        assert_eq!(part2, 123);
+        assert_eq!(part2, Ok(123));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules = Rules::from([(1, 2), (2, 3), (3, 4), (4, 2), (5, 1)]);
+        assert_eq!(sort_pages(&[3, 1, 5], &rules), Ok(vec![3, 5, 1]));
+
+        let cycle = sort_pages(&[4, 1, 3, 2], &rules).unwrap_err();
+        assert_eq!(cycle, Cycle(vec![2, 3, 4]));
+        assert_eq!(
+            cycle.to_string(),
+            "ordering rules form a cycle: 2 -> 3 -> 4 -> 2"
+        );
+    }
+
+    #[test]
+    fn test_rule_index() -> std::io::Result<()> {
+        let (rules, pages) = parse_input("../test_input/day05test.txt")?;
+        let mut index = RuleIndex::new(&rules);
+        for p in &pages {
+            assert_eq!(index.is_valid(p), is_valid_pairwise(p, &rules));
+        }
+
+        // Memory only depends on how many pages have rules, not on their IDs
+        let mut index = RuleIndex::new(&Rules::from([(1_000_000_000, 7), (7, 99_999)]));
+        assert!(index.is_valid(&[1_000_000_000, 3, 7, 99_999]));
+        assert!(!index.is_valid(&[99_999, 1_000_000_000, 7]));
+
+        let input = generate_input(300, 2000, 20, 50);
+        let outcomes = compare::compare(&input, &STRATEGIES);
+        assert!(compare::disagreements(&outcomes).is_empty());
 
         Ok(())
     }
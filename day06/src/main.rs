@@ -1,18 +1,51 @@
 use std::cmp::max;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 type Position = (i32, i32);
 
+// Part 2 either re-walks the patrol for every candidate obstruction or follows a table of
+// jumps between obstacles; `--compare` checks the two agree
+const STRATEGIES: [compare::Strategy<Map>; 2] = [
+    compare::Strategy {
+        part: 2,
+        name: "step by step",
+        run: |map| {
+            let mut patrolled = map.clone();
+            part1(&mut patrolled);
+            Some(part2(&patrolled, (map.guard_pos, map.guard_dir)).to_string())
+        },
+    },
+    compare::Strategy {
+        part: 2,
+        name: "jump table",
+        run: |map| {
+            let mut patrolled = map.clone();
+            part1(&mut patrolled);
+            Some(part2_jump_table(&patrolled, (map.guard_pos, map.guard_dir)).to_string())
+        },
+    },
+];
+
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let mut map = Map::from_file("input/day06.txt")?;
-    let initial_guard_pos = map.guard_pos;
-
-    while map.move_guard() {}
+    if repl::start(GuardSimulation::new(&map), &args)? {
+        return Ok(());
+    }
+    if compare::start(&map, &STRATEGIES, &args) {
+        return Ok(());
+    }
+    // Every guard on the map patrolling at once
+    if args.contains(&String::from("--guards")) {
+        print!("{}", patrol_report(&map));
+        return Ok(());
+    }
+    let start = (map.guard_pos, map.guard_dir);
 
     println!("Part1: {}", part1(&mut map));
-    println!("Part2: {}", part2(&map, initial_guard_pos));
+    println!("Part2: {}", part2_jump_table(&map, start));
 
     Ok(())
 }
@@ -23,14 +56,15 @@ fn part1(map: &mut Map) -> usize {
     map.visited.len()
 }
 
-fn part2(map: &Map, start_pos: Position) -> usize {
+fn part2(map: &Map, start: State) -> usize {
     let mut works = HashSet::new();
 
-    for (x, y) in map.visited.iter() {
+    // The obstruction can't go where the guard starts
+    for (x, y) in map.visited.iter().filter(|&&pos| pos != start.0) {
         let mut test_obstacles = map.obstacles.clone();
         test_obstacles.insert((*x, *y));
-        let mut m = Map::new(map.x_size, map.y_size, start_pos, &test_obstacles);
-        if m.check_for_loop(map.visited.len() * 2) {
+        let mut m = Map::new(map.x_size, map.y_size, start, &test_obstacles);
+        if m.find_loop().is_some() {
             works.insert((*x, *y));
         }
     }
@@ -38,7 +72,39 @@ fn part2(map: &Map, start_pos: Position) -> usize {
     works.len()
 }
 
-#[derive(Debug)]
+// Follows only the guard's turns using a jump table, trying the candidate obstructions
+// on every available core
+fn part2_jump_table(map: &Map, start: State) -> usize {
+    let table = JumpTable::new(map);
+    let candidates = map
+        .visited
+        .iter()
+        .filter(|&&pos| pos != start.0)
+        .copied()
+        .collect::<Vec<_>>();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let table = &table;
+                scope.spawn(move || {
+                    let mut seen = vec![0; map.x_size * map.y_size];
+                    chunk
+                        .iter()
+                        .filter(|&&obstacle| table.loops_with(start, obstacle, &mut seen))
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
     North,
     East,
@@ -55,9 +121,48 @@ impl Direction {
             Direction::West => *self = Self::North,
         }
     }
+
+    fn from_glyph(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    fn glyph(&self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn offset(&self) -> Position {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    fn is_vertical(&self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
 }
 
-#[derive(Debug)]
+// Where the guard is and which way it's facing, which fully determines where it goes next
+type State = (Position, Direction);
+
+#[derive(Debug, Clone)]
 struct Map {
     guard_pos: Position,
     guard_dir: Direction,
@@ -65,35 +170,37 @@ struct Map {
     y_size: usize,
     obstacles: HashSet<Position>,
     visited: HashSet<Position>,
+    // Where every guard on the map starts, in reading order. The first one is the guard
+    // the puzzle is about.
+    guards: Vec<State>,
 }
 
 impl Map {
-    fn new(
-        x_size: usize,
-        y_size: usize,
-        guard_pos: Position,
-        obstacles: &HashSet<Position>,
-    ) -> Self {
+    fn new(x_size: usize, y_size: usize, guard: State, obstacles: &HashSet<Position>) -> Self {
         let mut visited = HashSet::new();
-        visited.insert(guard_pos);
+        visited.insert(guard.0);
 
         Self {
-            guard_pos,
-            guard_dir: Direction::North,
+            guard_pos: guard.0,
+            guard_dir: guard.1,
             x_size,
             y_size,
             obstacles: obstacles.clone(),
             visited,
+            guards: vec![guard],
         }
     }
 
     fn from_file(path: &str) -> std::io::Result<Self> {
         let f = File::open(path)?;
-        let reader = BufReader::new(f);
+        Ok(Self::from_reader(BufReader::new(f)))
+    }
+
+    fn from_reader(reader: impl BufRead) -> Self {
         let lines = reader.lines();
 
         let mut obstacles: HashSet<Position> = HashSet::new();
-        let mut guard_position: Position = (0, 0);
+        let mut guards = Vec::new();
         let mut x_size = 0;
         let mut y_size = 0;
 
@@ -106,62 +213,406 @@ impl Map {
             }
             for (x, c) in line.chars().enumerate() {
                 x_size = max(x_size, x);
-                match c {
-                    '#' => _ = obstacles.insert((x as i32, y as i32)),
-                    '^' => guard_position = (x as i32, y as i32),
-                    _ => continue,
+                if c == '#' {
+                    obstacles.insert((x as i32, y as i32));
+                } else if let Some(dir) = Direction::from_glyph(c) {
+                    guards.push(((x as i32, y as i32), dir));
                 }
             }
             y_size = max(y_size, y);
         }
 
-        Ok(Self::new(
-            x_size + 1,
-            y_size + 1,
-            guard_position,
-            &obstacles,
-        ))
+        let guard = guards
+            .first()
+            .copied()
+            .unwrap_or(((0, 0), Direction::North));
+        let mut map = Self::new(x_size + 1, y_size + 1, guard, &obstacles);
+        if !guards.is_empty() {
+            map.guards = guards;
+        }
+
+        map
+    }
+
+    // The same map with only the given guard on it
+    fn with_guard(&self, guard: State) -> Self {
+        Self::new(self.x_size, self.y_size, guard, &self.obstacles)
     }
 
     fn move_guard(&mut self) -> bool {
-        let pos_update = match self.guard_dir {
-            Direction::North => (0, -1),
-            Direction::East => (1, 0),
-            Direction::South => (0, 1),
-            Direction::West => (-1, 0),
-        };
-        let next_pos = (
-            self.guard_pos.0 + pos_update.0,
-            self.guard_pos.1 + pos_update.1,
-        );
-        if next_pos.0 < 0
-            || next_pos.0 >= self.x_size as i32
-            || next_pos.1 < 0
-            || next_pos.1 >= self.y_size as i32
-        {
-            return false;
-        }
-        if self.obstacles.contains(&next_pos) {
-            self.guard_dir.turn_right();
-            return self.move_guard();
-        }
-        self.visited.insert(next_pos);
-        self.guard_pos = next_pos;
+        // A guard boxed in on all four sides turns all the way round and stays put
+        for _ in 0..4 {
+            let pos_update = self.guard_dir.offset();
+            let next_pos = (
+                self.guard_pos.0 + pos_update.0,
+                self.guard_pos.1 + pos_update.1,
+            );
+            if !self.contains(next_pos) {
+                return false;
+            }
+            if self.obstacles.contains(&next_pos) {
+                self.guard_dir.turn_right();
+                continue;
+            }
+            self.visited.insert(next_pos);
+            self.guard_pos = next_pos;
+            break;
+        }
+
         true
     }
 
-    fn check_for_loop(&mut self, max_iter: usize) -> bool {
-        let mut last_visited = 1;
-        let mut count = 0;
+    fn contains(&self, pos: Position) -> bool {
+        (0..self.x_size as i32).contains(&pos.0) && (0..self.y_size as i32).contains(&pos.1)
+    }
+
+    // Patrol until the guard either leaves the map or returns to a state it has already
+    // been in, in which case the states making up the loop are returned in order
+    fn find_loop(&mut self) -> Option<Vec<State>> {
+        let mut path = vec![(self.guard_pos, self.guard_dir)];
+        let mut seen = HashMap::from([(path[0], 0)]);
         while self.move_guard() {
-            count += 1;
-            if self.visited.len() == last_visited && count > max_iter {
-                return true;
+            let state = (self.guard_pos, self.guard_dir);
+            if let Some(&start) = seen.get(&state) {
+                return Some(path.split_off(start));
             }
-            last_visited = self.visited.len();
+            seen.insert(state, path.len());
+            path.push(state);
         }
 
-        false
+        None
+    }
+
+    // The map with a loop drawn over it the way the puzzle does: `|` and `-` for cells
+    // crossed vertically or horizontally, `+` for both, including where the guard turns
+    fn render_loop(&self, cycle: &[State]) -> String {
+        let mut vertical = HashSet::new();
+        let mut horizontal = HashSet::new();
+        for (i, &(pos, dir)) in cycle.iter().enumerate() {
+            let (_, next_dir) = cycle[(i + 1) % cycle.len()];
+            for d in [dir, next_dir] {
+                if d.is_vertical() {
+                    vertical.insert(pos);
+                } else {
+                    horizontal.insert(pos);
+                }
+            }
+        }
+
+        let mut result = String::new();
+        for y in 0..self.y_size as i32 {
+            for x in 0..self.x_size as i32 {
+                let c = match (vertical.contains(&(x, y)), horizontal.contains(&(x, y))) {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    _ if self.obstacles.contains(&(x, y)) => '#',
+                    _ => '.',
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+        result.pop();
+
+        result
+    }
+
+    fn render(&self) -> String {
+        let mut result = String::new();
+        for y in 0..self.y_size as i32 {
+            for x in 0..self.x_size as i32 {
+                let c = if (x, y) == self.guard_pos {
+                    self.guard_dir.glyph()
+                } else if self.obstacles.contains(&(x, y)) {
+                    '#'
+                } else if self.visited.contains(&(x, y)) {
+                    'X'
+                } else {
+                    '.'
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+        result.pop();
+
+        result
+    }
+}
+
+// For every cell and direction, where a guard walking that way stops: the cell in front of
+// the next obstacle, or None if it walks off the map
+struct JumpTable {
+    x_size: usize,
+    stops: Vec<[Option<Position>; 4]>,
+}
+
+impl JumpTable {
+    fn new(map: &Map) -> Self {
+        let cells = map.x_size * map.y_size;
+        let mut stops = vec![[None; 4]; cells];
+        for dir in [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ] {
+            // Visit cells so that the one in front has always been filled in already
+            let order = match dir {
+                Direction::North | Direction::West => (0..cells).collect::<Vec<_>>(),
+                Direction::East | Direction::South => (0..cells).rev().collect(),
+            };
+            let (dx, dy) = dir.offset();
+            for cell in order {
+                let pos = ((cell % map.x_size) as i32, (cell / map.x_size) as i32);
+                let next_pos = (pos.0 + dx, pos.1 + dy);
+                stops[cell][dir.index()] = if !map.contains(next_pos) {
+                    None
+                } else if map.obstacles.contains(&next_pos) {
+                    Some(pos)
+                } else {
+                    stops[next_pos.1 as usize * map.x_size + next_pos.0 as usize][dir.index()]
+                };
+            }
+        }
+
+        Self {
+            x_size: map.x_size,
+            stops,
+        }
+    }
+
+    fn cell(&self, pos: Position) -> usize {
+        pos.1 as usize * self.x_size + pos.0 as usize
+    }
+
+    // Whether the guard gets stuck in a loop once an extra obstacle is placed. `seen` holds a
+    // bit per direction for every cell, and is left cleared for the next call.
+    fn loops_with(&self, start: State, obstacle: Position, seen: &mut [u8]) -> bool {
+        let (mut pos, mut dir) = start;
+        let mut touched = Vec::new();
+
+        let looped = loop {
+            let mut stop = self.stops[self.cell(pos)][dir.index()];
+
+            // The extra obstacle cuts the walk short if it's between here and the stop
+            let (dx, dy) = dir.offset();
+            let ahead = (obstacle.0 - pos.0, obstacle.1 - pos.1);
+            let distance = ahead.0 * dx + ahead.1 * dy;
+            if distance > 0 && ahead == (dx * distance, dy * distance) {
+                let stop_distance = stop.map(|s| (s.0 - pos.0) * dx + (s.1 - pos.1) * dy);
+                if stop_distance.is_none_or(|d| distance <= d) {
+                    stop = Some((obstacle.0 - dx, obstacle.1 - dy));
+                }
+            }
+
+            let Some(stop) = stop else {
+                break false;
+            };
+            let cell = self.cell(stop);
+            let bit = 1 << dir.index();
+            if seen[cell] & bit != 0 {
+                break true;
+            }
+            if seen[cell] == 0 {
+                touched.push(cell);
+            }
+            seen[cell] |= bit;
+
+            pos = stop;
+            dir.turn_right();
+        };
+
+        for cell in touched {
+            seen[cell] = 0;
+        }
+
+        looped
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fate {
+    // Walked off the map on this tick, from this state
+    Exited { tick: usize, from: State },
+    // Got back to a state it had already been in on this tick
+    Looping { tick: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GuardReport {
+    start: State,
+    visited: HashSet<Position>,
+    fate: Fate,
+}
+
+// Guards ending a tick on the same cell, or walking through each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Collision {
+    tick: usize,
+    pos: Position,
+    guards: Vec<usize>,
+}
+
+// Move every guard a step per tick until each one has either left the map or been found
+// to be looping. Guards don't block each other, they only collide.
+fn patrol(map: &Map) -> (Vec<GuardReport>, Vec<Collision>) {
+    let mut walkers = map
+        .guards
+        .iter()
+        .map(|&guard| map.with_guard(guard))
+        .collect::<Vec<_>>();
+    let mut seen = map
+        .guards
+        .iter()
+        .map(|&guard| HashSet::from([guard]))
+        .collect::<Vec<_>>();
+    let mut fates: Vec<Option<Fate>> = vec![None; walkers.len()];
+    let mut collisions = Vec::new();
+
+    let mut tick = 0;
+    while fates.iter().any(|fate| fate.is_none()) {
+        tick += 1;
+        let before = walkers.iter().map(|w| w.guard_pos).collect::<Vec<_>>();
+
+        for (i, walker) in walkers.iter_mut().enumerate() {
+            if matches!(fates[i], Some(Fate::Exited { .. })) {
+                continue;
+            }
+            let from = (walker.guard_pos, walker.guard_dir);
+            if !walker.move_guard() {
+                fates[i] = Some(Fate::Exited { tick, from });
+                continue;
+            }
+            if !seen[i].insert((walker.guard_pos, walker.guard_dir)) && fates[i].is_none() {
+                fates[i] = Some(Fate::Looping { tick });
+            }
+        }
+
+        let on_map = (0..walkers.len())
+            .filter(|&i| !matches!(fates[i], Some(Fate::Exited { .. })))
+            .collect::<Vec<_>>();
+        let mut cells: BTreeMap<Position, Vec<usize>> = BTreeMap::new();
+        for &i in &on_map {
+            cells.entry(walkers[i].guard_pos).or_default().push(i);
+        }
+        for (pos, guards) in cells {
+            if guards.len() > 1 {
+                collisions.push(Collision { tick, pos, guards });
+            }
+        }
+        for (n, &i) in on_map.iter().enumerate() {
+            for &j in &on_map[n + 1..] {
+                let (pos_i, pos_j) = (walkers[i].guard_pos, walkers[j].guard_pos);
+                if pos_i != pos_j && before[i] == pos_j && before[j] == pos_i {
+                    collisions.push(Collision {
+                        tick,
+                        pos: pos_i,
+                        guards: vec![i, j],
+                    });
+                }
+            }
+        }
+    }
+
+    let reports = walkers
+        .into_iter()
+        .zip(fates)
+        .zip(&map.guards)
+        .map(|((walker, fate), &start)| GuardReport {
+            start,
+            visited: walker.visited,
+            fate: fate.unwrap(),
+        })
+        .collect();
+
+    (reports, collisions)
+}
+
+fn patrol_report(map: &Map) -> String {
+    let (reports, collisions) = patrol(map);
+
+    let mut result = String::new();
+    for (i, report) in reports.iter().enumerate() {
+        let fate = match report.fate {
+            Fate::Exited { tick, from } => format!(
+                "left the map on tick {} from {:?} facing {:?}",
+                tick, from.0, from.1
+            ),
+            Fate::Looping { tick } => format!("stuck in a loop from tick {}", tick),
+        };
+        result.push_str(&format!(
+            "guard {} starting at {:?} facing {:?}: visited {} cells, {}\n",
+            i,
+            report.start.0,
+            report.start.1,
+            report.visited.len(),
+            fate
+        ));
+    }
+    for collision in collisions {
+        result.push_str(&format!(
+            "collision on tick {} at {:?} between guards {:?}\n",
+            collision.tick, collision.pos, collision.guards
+        ));
+    }
+
+    result
+}
+
+struct GuardSimulation {
+    initial: Map,
+    map: Map,
+}
+
+impl GuardSimulation {
+    fn new(map: &Map) -> Self {
+        Self {
+            initial: map.clone(),
+            map: map.clone(),
+        }
+    }
+}
+
+impl repl::Simulation for GuardSimulation {
+    fn step(&mut self) -> bool {
+        self.map.move_guard()
+    }
+
+    fn show(&self) -> String {
+        self.map.render()
+    }
+
+    fn query(&self, args: &[&str]) -> Option<String> {
+        match args {
+            ["guard"] => Some(format!(
+                "{:?} facing {:?}",
+                self.map.guard_pos, self.map.guard_dir
+            )),
+            ["visited"] => Some(self.map.visited.len().to_string()),
+            ["loop"] => match self.map.clone().find_loop() {
+                Some(cycle) => Some(self.map.render_loop(&cycle)),
+                None => Some(String::from("the guard leaves the map")),
+            },
+            [x, y] => {
+                let pos = (x.parse().ok()?, y.parse().ok()?);
+                Some(format!(
+                    "obstacle: {}, visited: {}",
+                    self.map.obstacles.contains(&pos),
+                    self.map.visited.contains(&pos)
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn queries(&self) -> &'static str {
+        "  guard      position and direction of the guard\n  visited    number of cells visited\n  loop       the loop the guard is stuck in, if any\n  <x> <y>    what is at a cell"
+    }
+
+    fn reset(&mut self) {
+        self.map = self.initial.clone();
     }
 }
 
@@ -180,10 +631,122 @@ mod day06_tests {
     #[test]
     fn test_part2() -> std::io::Result<()> {
         let mut map = Map::from_file("../test_input/day06test.txt")?;
-        let init_guard_pos = map.guard_pos;
+        let start = (map.guard_pos, map.guard_dir);
         part1(&mut map);
-        assert_eq!(part2(&mut map, init_guard_pos), 6);
+        assert_eq!(part2(&map, start), 6);
+
+        Ok(())
+    }
+
+    fn map_from_str(text: &str) -> Map {
+        Map::from_reader(text.as_bytes())
+    }
+
+    #[test]
+    fn test_find_loop() {
+        let mut map = map_from_str(".#...\n....#\n.....\n#^...\n...#.");
+        let cycle = map.find_loop().unwrap();
+        assert_eq!(cycle.len(), 8);
+        assert_eq!(cycle[0], ((1, 2), Direction::North));
+        assert_eq!(map.render_loop(&cycle), ".#...\n.+-+#\n.|.|.\n#+-+.\n...#.");
+
+        // Crosses its own path at the start, but then walks off the map
+        let mut map = map_from_str(".#....\n.....#\n......\n.^....\n....#.");
+        assert_eq!(map.find_loop(), None);
+        assert!(map.visited.contains(&(0, 3)));
+
+        // Boxed in, so it can only spin on the spot
+        let mut map = map_from_str(".#.\n#^#\n.#.");
+        assert_eq!(map.find_loop(), Some(vec![((1, 1), Direction::North)]));
+
+        // Has to turn twice in a row at a dead end and walk back the way it came
+        let mut map = map_from_str("#.\n.#\n^.\n#.");
+        assert_eq!(map.find_loop(), None);
+    }
+
+    #[test]
+    fn test_strategies_agree() -> std::io::Result<()> {
+        let map = Map::from_file("../test_input/day06test.txt")?;
+        let outcomes = compare::compare(&map, &STRATEGIES);
+        assert_eq!(outcomes[1].answer.as_deref(), Some("6"));
+        assert!(compare::disagreements(&outcomes).is_empty());
+
+        // The guard has to leave these for part 1 to finish
+        for layout in [
+            ".#....\n.....#\n......\n.^....\n....#.",
+            "#.\n.#\n^.\n#.",
+            "..#.\n...#\n.#..\n..^.\n....",
+        ] {
+            let outcomes = compare::compare(&map_from_str(layout), &STRATEGIES);
+            assert!(
+                compare::disagreements(&outcomes).is_empty(),
+                "{}\n{:?}",
+                layout,
+                outcomes
+            );
+        }
 
         Ok(())
     }
+
+    #[test]
+    fn test_guards() {
+        let map = map_from_str(".v..\n>..<\n....");
+        assert_eq!(
+            map.guards,
+            vec![
+                ((1, 0), Direction::South),
+                ((0, 1), Direction::East),
+                ((3, 1), Direction::West)
+            ]
+        );
+        assert_eq!(map.guard_dir, Direction::South);
+
+        let (reports, collisions) = patrol(&map);
+        assert_eq!(
+            reports.iter().map(|r| r.fate).collect::<Vec<_>>(),
+            vec![
+                Fate::Exited {
+                    tick: 3,
+                    from: ((1, 2), Direction::South)
+                },
+                Fate::Exited {
+                    tick: 4,
+                    from: ((3, 1), Direction::East)
+                },
+                Fate::Exited {
+                    tick: 4,
+                    from: ((0, 1), Direction::West)
+                },
+            ]
+        );
+        assert_eq!(reports[1].visited.len(), 4);
+        assert_eq!(
+            collisions,
+            vec![
+                Collision {
+                    tick: 1,
+                    pos: (1, 1),
+                    guards: vec![0, 1]
+                },
+                Collision {
+                    tick: 2,
+                    pos: (2, 1),
+                    guards: vec![1, 2]
+                },
+            ]
+        );
+
+        let map = map_from_str(".#...\n....#\n.....\n#^...\n...#<");
+        let (reports, collisions) = patrol(&map);
+        assert_eq!(reports[0].fate, Fate::Looping { tick: 9 });
+        assert_eq!(
+            reports[1].fate,
+            Fate::Exited {
+                tick: 3,
+                from: ((4, 2), Direction::North)
+            }
+        );
+        assert!(collisions.is_empty());
+    }
 }
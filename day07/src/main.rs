@@ -1,21 +1,61 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 struct Equation(u64, Vec<u64>);
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let equations = parse_equations("input/day07.txt")?;
 
+    // Any other set of operators, e.g. `--ops +,-,*,/ --precedence`
+    let custom = args
+        .iter()
+        .any(|a| a == "--ops" || a == "--precedence")
+        .then(|| Operators::from_args(&args));
+    let custom = match custom {
+        Some(Ok(operators)) => Some(operators),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    // Show how each equation can be made true
+    if args.contains(&String::from("--explain")) {
+        let operators = custom.unwrap_or_else(Operators::part2);
+        for equation in &equations {
+            match solve(equation, &operators) {
+                Some(operators) => {
+                    println!("{}: {}", equation.0, expression(&equation.1, &operators))
+                }
+                None => println!("{}: impossible", equation.0),
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(operators) = custom {
+        let total = equations
+            .iter()
+            .filter(|&e| is_valid(e, &operators))
+            .map(|e| e.0)
+            .sum::<u64>();
+        println!("Total = {}", total);
+        return Ok(());
+    }
+
     let part1_start = std::time::Instant::now();
     let part1 = equations
         .iter()
-        .filter(|&e| is_valid(e, false))
+        .filter(|&e| is_valid(e, &Operators::part1()))
         .map(|e| e.0)
         .sum::<u64>();
     let part2_start = std::time::Instant::now();
     let part2 = equations
         .iter()
-        .filter(|&e| is_valid(e, true))
+        .filter(|&e| is_valid(e, &Operators::part2()))
         .map(|e| e.0)
         .sum::<u64>();
     let end = std::time::Instant::now();
@@ -61,32 +101,293 @@ fn parse_equations(path: &str) -> std::io::Result<Vec<Equation>> {
     Ok(equations)
 }
 
-fn is_valid(equation: &Equation, part2: bool) -> bool {
-    match equation.1.as_slice() {
-        [num] => *num == equation.0,
-        [num1, num2] => {
-            equation.0 == num1 + num2
-                || equation.0 == num1 * num2
-                || (part2 && equation.0 == concat(*num1, *num2))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Sub,
+    Mul,
+    // Integer division, rounding down
+    Div,
+    Pow,
+    // Concatenation of the digits in the given base
+    Concat(u64),
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::Pow => write!(f, "^"),
+            Operator::Concat(10) => write!(f, "||"),
+            Operator::Concat(base) => write!(f, "||{}", base),
+        }
+    }
+}
+
+impl Operator {
+    // The same symbols as `Display`, with `||<base>` for concatenation in other bases
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "+" => Some(Operator::Add),
+            "-" => Some(Operator::Sub),
+            "*" => Some(Operator::Mul),
+            "/" => Some(Operator::Div),
+            "^" => Some(Operator::Pow),
+            "||" => Some(Operator::Concat(10)),
+            _ => match s.strip_prefix("||")?.parse() {
+                Ok(base) if base >= 2 => Some(Operator::Concat(base)),
+                _ => None,
+            },
+        }
+    }
+
+    // None on overflow, a negative result or division by zero
+    fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div => a.checked_div(b),
+            Operator::Pow => a.checked_pow(b.try_into().ok()?),
+            Operator::Concat(base) => a.checked_mul(shift(b, *base)?)?.checked_add(b),
+        }
+    }
+
+    // The left operand that gives `target` with `last` on the right, for the operators
+    // with a unique one
+    fn undo(&self, target: u64, last: u64) -> Option<u64> {
+        match self {
+            Operator::Add => target.checked_sub(last),
+            Operator::Sub => target.checked_add(last),
+            Operator::Mul if last != 0 && target.is_multiple_of(last) => Some(target / last),
+            Operator::Concat(base) => {
+                let shift = shift(last, *base)?;
+                (target % shift == last).then_some(target / shift)
+            }
+            _ => None,
         }
-        [num1, num2, rest @ ..] => {
-            let mut vec1 = vec![num1 + num2];
-            let mut vec2 = vec![num1 * num2];
-            let mut vec3 = vec![concat(*num1, *num2)];
-            vec1.extend(rest);
-            vec2.extend(rest);
-            vec3.extend(rest);
+    }
 
-            is_valid(&Equation(equation.0, vec1), part2)
-                || is_valid(&Equation(equation.0, vec2), part2)
-                || (part2 && is_valid(&Equation(equation.0, vec3), part2))
+    // Whether `undo` finds every solution. Division and powers can have many left operands
+    // for the same result.
+    fn is_invertible(&self) -> bool {
+        matches!(
+            self,
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Concat(_)
+        )
+    }
+
+    // How tightly the operator binds under standard precedence. Concatenation binds
+    // tightest, since `12 || 3` reads as the single number 123.
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div => 2,
+            Operator::Pow => 3,
+            Operator::Concat(_) => 4,
         }
-        _ => false,
     }
 }
 
-fn concat(num1: u64, num2: u64) -> u64 {
-    num1 * 10_u64.pow(num2.to_string().len() as u32) + num2
+// What to multiply by to make room for `num`'s digits in the given base
+fn shift(num: u64, base: u64) -> Option<u64> {
+    let mut shift = base;
+    while shift <= num {
+        shift = shift.checked_mul(base)?;
+    }
+
+    Some(shift)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    // The puzzle's rules
+    LeftToRight,
+    // Higher precedence first, then left to right except for `^`
+    Precedence,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Operators {
+    enabled: Vec<Operator>,
+    order: Order,
+}
+
+impl Operators {
+    fn part1() -> Self {
+        Self {
+            enabled: vec![Operator::Add, Operator::Mul],
+            order: Order::LeftToRight,
+        }
+    }
+
+    fn part2() -> Self {
+        Self {
+            enabled: vec![Operator::Add, Operator::Mul, Operator::Concat(10)],
+            order: Order::LeftToRight,
+        }
+    }
+
+    // `--ops` takes a comma separated list of operators, part 2's by default, and
+    // `--precedence` switches to standard precedence
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut operators = Self::part2();
+        if let Some(i) = args.iter().position(|a| a == "--ops") {
+            let list = args.get(i + 1).ok_or("--ops expects a list of operators")?;
+            operators.enabled = list
+                .split(',')
+                .map(|s| Operator::parse(s).ok_or(format!("unknown operator {}", s)))
+                .collect::<Result<_, _>>()?;
+        }
+        if args.contains(&String::from("--precedence")) {
+            operators.order = Order::Precedence;
+        }
+
+        Ok(operators)
+    }
+}
+
+// None if any step overflows or otherwise has no answer
+fn evaluate(numbers: &[u64], operators: &[Operator], order: Order) -> Option<u64> {
+    let (&first, rest) = numbers.split_first()?;
+    match order {
+        Order::LeftToRight => operators
+            .iter()
+            .zip(rest)
+            .try_fold(first, |acc, (operator, &num)| operator.apply(acc, num)),
+        Order::Precedence => {
+            let mut values = vec![first];
+            let mut pending: Vec<Operator> = Vec::new();
+            let reduce = |values: &mut Vec<u64>, operator: Operator| {
+                let b = values.pop()?;
+                let a = values.pop()?;
+                values.push(operator.apply(a, b)?);
+                Some(())
+            };
+            for (&operator, &num) in operators.iter().zip(rest) {
+                while let Some(&top) = pending.last() {
+                    let binds_first = top.precedence() > operator.precedence()
+                        || (top.precedence() == operator.precedence() && operator != Operator::Pow);
+                    if !binds_first {
+                        break;
+                    }
+                    pending.pop();
+                    reduce(&mut values, top)?;
+                }
+                pending.push(operator);
+                values.push(num);
+            }
+            while let Some(top) = pending.pop() {
+                reduce(&mut values, top)?;
+            }
+
+            values.pop()
+        }
+    }
+}
+
+fn is_valid(equation: &Equation, operators: &Operators) -> bool {
+    solve(equation, operators).is_some()
+}
+
+// The operators to put between the numbers to make the equation true, if there are any
+fn solve(equation: &Equation, operators: &Operators) -> Option<Vec<Operator>> {
+    let backwards = operators.order == Order::LeftToRight
+        && operators.enabled.iter().all(|o| o.is_invertible());
+    if !backwards {
+        return search(&equation.1, operators, &|value| value == equation.0);
+    }
+
+    let mut found = Vec::new();
+    solve_backwards(equation.0, &equation.1, operators, &mut found).then_some(found)
+}
+
+// Undo the last operation instead of trying every combination forwards: e.g. the last
+// number can only have been added if it's no bigger than the target, multiplied if it
+// divides it, and concatenated if the target ends in it. Most branches die straight away.
+fn solve_backwards(
+    target: u64,
+    numbers: &[u64],
+    operators: &Operators,
+    found: &mut Vec<Operator>,
+) -> bool {
+    let (last, rest) = match numbers {
+        [] => return false,
+        [num] => return *num == target,
+        [rest @ .., last] => (*last, rest),
+    };
+
+    for &operator in &operators.enabled {
+        if operator == Operator::Mul && last == 0 && target == 0 {
+            // Anything times zero is zero, as long as the rest can be worked out at all
+            if let Some(rest_operators) = search(rest, operators, &|_| true) {
+                found.extend(rest_operators);
+                found.push(operator);
+                return true;
+            }
+        }
+        let Some(previous) = operator.undo(target, last) else {
+            continue;
+        };
+        if solve_backwards(previous, rest, operators, found) {
+            found.push(operator);
+            return true;
+        }
+    }
+
+    false
+}
+
+// Try every combination of operators until one gives an accepted value
+fn search(
+    numbers: &[u64],
+    operators: &Operators,
+    accept: &dyn Fn(u64) -> bool,
+) -> Option<Vec<Operator>> {
+    fn extend(
+        numbers: &[u64],
+        operators: &Operators,
+        accept: &dyn Fn(u64) -> bool,
+        chosen: &mut Vec<Operator>,
+    ) -> bool {
+        // Left to right, a step that fails rules out everything after it
+        if operators.order == Order::LeftToRight
+            && evaluate(&numbers[..=chosen.len()], chosen, operators.order).is_none()
+        {
+            return false;
+        }
+        if chosen.len() + 1 == numbers.len() {
+            return evaluate(numbers, chosen, operators.order).is_some_and(accept);
+        }
+        for &operator in &operators.enabled {
+            chosen.push(operator);
+            if extend(numbers, operators, accept, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
+
+        false
+    }
+
+    if numbers.is_empty() {
+        return None;
+    }
+    let mut chosen = Vec::new();
+    extend(numbers, operators, accept, &mut chosen).then_some(chosen)
+}
+
+// Written out the way the puzzle does, e.g. `81 + 40 * 27`
+fn expression(numbers: &[u64], operators: &[Operator]) -> String {
+    let mut result = numbers[0].to_string();
+    for (operator, num) in operators.iter().zip(&numbers[1..]) {
+        result.push_str(&format!(" {} {}", operator, num));
+    }
+
+    result
 }
 
 #[cfg(test)]
@@ -95,8 +396,12 @@ mod tests {
 
     #[test]
     fn test_concat() {
-        assert_eq!(concat(11, 12), 1112);
-        assert_eq!(concat(123, 45), 12345);
+        assert_eq!(Operator::Concat(10).apply(11, 12), Some(1112));
+        assert_eq!(Operator::Concat(10).apply(123, 45), Some(12345));
+        assert_eq!(Operator::Concat(10).apply(5, 0), Some(50));
+        assert_eq!(Operator::Concat(2).apply(0b101, 0b11), Some(0b10111));
+        assert_eq!(Operator::Concat(10).apply(u64::MAX / 10, 12), None);
+        assert_eq!(Operator::Mul.apply(u64::MAX, 2), None);
     }
 
     #[test]
@@ -104,7 +409,7 @@ mod tests {
         let equations = parse_equations("../test_input/day07test.txt")?;
         let part1 = equations
             .iter()
-            .filter(|&e| is_valid(e, false))
+            .filter(|&e| is_valid(e, &Operators::part1()))
             .map(|e| e.0)
             .sum::<u64>();
         assert_eq!(part1, 3749);
@@ -117,11 +422,92 @@ mod tests {
         let equations = parse_equations("../test_input/day07test.txt")?;
         let part2 = equations
             .iter()
-            .filter(|&e| is_valid(e, true))
+            .filter(|&e| is_valid(e, &Operators::part2()))
             .map(|e| e.0)
             .sum::<u64>();
         assert_eq!(part2, 11387);
 
         Ok(())
     }
+
+    #[test]
+    fn test_solve() -> std::io::Result<()> {
+        let equations = parse_equations("../test_input/day07test.txt")?;
+        let explained = equations
+            .iter()
+            .filter_map(|e| Some(expression(&e.1, &solve(e, &Operators::part2())?)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            explained,
+            vec![
+                "10 * 19",
+                "81 * 40 + 27",
+                "15 || 6",
+                "6 * 8 || 6 * 15",
+                "17 || 8 + 14",
+                "11 + 6 * 16 + 20"
+            ]
+        );
+
+        assert_eq!(
+            solve(&Equation(0, vec![3, 4, 0]), &Operators::part1()),
+            Some(vec![Operator::Add, Operator::Mul])
+        );
+        assert_eq!(
+            solve(&Equation(7, vec![3, 4, 0]), &Operators::part1()),
+            Some(vec![Operator::Add, Operator::Add])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_operators() {
+        let args = ["--ops", "+,-,*,/,^,||2", "--precedence"].map(String::from);
+        let operators = Operators::from_args(&args).unwrap();
+        assert_eq!(operators.enabled.len(), 6);
+        assert_eq!(operators.order, Order::Precedence);
+        assert!(Operators::from_args(&["--ops", "%"].map(String::from)).is_err());
+
+        use Operator::*;
+        assert_eq!(
+            evaluate(&[2, 3, 4], &[Add, Mul], Order::LeftToRight),
+            Some(20)
+        );
+        assert_eq!(
+            evaluate(&[2, 3, 4], &[Add, Mul], Order::Precedence),
+            Some(14)
+        );
+        assert_eq!(
+            evaluate(&[2, 3, 2], &[Pow, Pow], Order::Precedence),
+            Some(512)
+        );
+        assert_eq!(
+            evaluate(&[20, 3, 2], &[Sub, Sub], Order::Precedence),
+            Some(15)
+        );
+        assert_eq!(evaluate(&[3, 5], &[Sub], Order::LeftToRight), None);
+
+        let equation = Equation(14, vec![2, 3, 4]);
+        assert_eq!(solve(&equation, &Operators::part1()), None);
+        assert_eq!(solve(&equation, &operators), Some(vec![Add, Mul]));
+
+        // Subtraction is undone backwards, division only found by searching forwards
+        let operators = Operators {
+            enabled: vec![Sub, Div],
+            order: Order::LeftToRight,
+        };
+        assert_eq!(
+            solve(&Equation(2, vec![20, 6, 5]), &operators),
+            Some(vec![Sub, Div])
+        );
+        let operators = Operators {
+            enabled: vec![Add, Sub],
+            order: Order::LeftToRight,
+        };
+        assert_eq!(
+            solve(&Equation(1, vec![5, 2, 6]), &operators),
+            Some(vec![Add, Sub])
+        );
+    }
 }
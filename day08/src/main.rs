@@ -1,6 +1,6 @@
 use itertools::Itertools;
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -14,44 +14,157 @@ impl Position {
 }
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let map = Map::from_file("input/day08.txt")?;
 
+    // Include every grid point in line with a pair, not just whole steps between them
+    let lattice = args.contains(&String::from("--lattice"));
+    if args.contains(&String::from("--report")) || args.contains(&String::from("--render")) {
+        for (name, antinodes) in [
+            ("Part1", antinodes(&map)),
+            ("Part2", harmonic_antinodes(&map, lattice)),
+        ] {
+            println!("{}:", name);
+            if args.contains(&String::from("--report")) {
+                print!("{}", frequency_report(&map, &antinodes));
+            }
+            if args.contains(&String::from("--render")) {
+                let all = antinodes.values().flatten().map(|p| p.clone()).collect();
+                println!("{}", map.render(&all));
+            }
+        }
+        return Ok(());
+    }
+
+    let harmonic = if lattice {
+        Mode::Lattice
+    } else {
+        Mode::Harmonic
+    };
+
+    // Where antinodes of several pairs coincide
+    if args.contains(&String::from("--overlaps")) {
+        let analysis = Analysis::new(&map, harmonic);
+        for (p, sources) in analysis.overlaps() {
+            let pairs = sources
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{} {:?}-{:?}",
+                        s.frequency,
+                        (s.antennas.0 .0, s.antennas.0 .1),
+                        (s.antennas.1 .0, s.antennas.1 .1)
+                    )
+                })
+                .join(", ");
+            println!(
+                "{:?} {:?}: {}",
+                (p.0, p.1),
+                analysis.frequencies_at(p),
+                pairs
+            );
+        }
+        return Ok(());
+    }
+
+    // What if an antenna was moved, e.g. `--remove 8,1 --add A,3,3`
+    let remove = args.iter().position(|a| a == "--remove");
+    let add = args.iter().position(|a| a == "--add");
+    if remove.is_some() || add.is_some() {
+        let mut changed = map.clone();
+        if let Some(i) = remove {
+            let Some(p) = args.get(i + 1).and_then(|a| parse_position(a)) else {
+                eprintln!("--remove expects x,y");
+                std::process::exit(1);
+            };
+            if changed.without_antenna(&p).is_none() {
+                eprintln!("there is no antenna at {},{}", p.0, p.1);
+                std::process::exit(1);
+            }
+        }
+        if let Some(i) = add {
+            let parsed = args.get(i + 1).and_then(|a| {
+                let (frequency, p) = a.split_once(',')?;
+                Some((frequency.parse::<char>().ok()?, parse_position(p)?))
+            });
+            let Some((frequency, p)) = parsed else {
+                eprintln!("--add expects frequency,x,y");
+                std::process::exit(1);
+            };
+            changed.with_antenna(frequency, p);
+        }
+        for (name, mode) in [("Part1", Mode::Simple), ("Part2", harmonic)] {
+            let before = Analysis::new(&map, mode).count();
+            let after = Analysis::new(&changed, mode).count();
+            println!("{}: {} -> {}", name, before, after);
+        }
+        return Ok(());
+    }
+
     println!("Part1: {}", part1(&map));
-    println!("Part1: {}", part2(&map));
+    println!("Part2: {}", part2(&map));
 
     Ok(())
 }
 
 // Count antinodes for each frequency
 fn part1(map: &Map) -> usize {
-    let mut result = HashSet::new();
+    count_distinct(&antinodes(map))
+}
+
+fn part2(map: &Map) -> usize {
+    count_distinct(&harmonic_antinodes(map, false))
+}
+
+// Antinodes of any frequency, counting shared locations once
+fn count_distinct(antinodes: &HashMap<char, HashSet<Position>>) -> usize {
+    antinodes.values().flatten().collect::<HashSet<_>>().len()
+}
+
+fn antinodes(map: &Map) -> HashMap<char, HashSet<Position>> {
+    let mut result = HashMap::new();
     // Check each pair of each frequency and add their antinodes
-    for v in map.antennas.values() {
+    for (&frequency, v) in &map.antennas {
+        let found: &mut HashSet<Position> = result.entry(frequency).or_default();
         for pair in v.iter().combinations(2) {
             let (antinode0, antinode1) = find_antinodes(pair[0], pair[1]);
             if map.in_map(&antinode0) {
-                result.insert(antinode0);
+                found.insert(antinode0);
             }
             if map.in_map(&antinode1) {
-                result.insert(antinode1);
+                found.insert(antinode1);
             }
         }
     }
 
-    result.len()
+    result
 }
 
-fn part2(map: &Map) -> usize {
-    let mut result = HashSet::new();
-    for v in map.antennas.values() {
+fn harmonic_antinodes(map: &Map, lattice: bool) -> HashMap<char, HashSet<Position>> {
+    let mut result = HashMap::new();
+    for (&frequency, v) in &map.antennas {
+        let found: &mut HashSet<Position> = result.entry(frequency).or_default();
         for pair in v.iter().combinations(2) {
-            for antinode in find_harmonic_antinodes(pair[0], pair[1], map) {
-                result.insert(antinode);
-            }
+            found.extend(find_harmonic_antinodes(pair[0], pair[1], map, lattice));
         }
     }
 
-    result.len()
+    result
+}
+
+fn frequency_report(map: &Map, antinodes: &HashMap<char, HashSet<Position>>) -> String {
+    let mut result = String::new();
+    for frequency in antinodes.keys().sorted() {
+        result.push_str(&format!(
+            "{}: {} antennas, {} antinodes\n",
+            frequency,
+            map.antennas[frequency].len(),
+            antinodes[frequency].len()
+        ));
+    }
+    result.push_str(&format!("Total: {} antinodes\n", count_distinct(antinodes)));
+
+    result
 }
 
 fn find_antinodes(p0: &Position, p1: &Position) -> (Position, Position) {
@@ -62,9 +175,30 @@ fn find_antinodes(p0: &Position, p1: &Position) -> (Position, Position) {
     )
 }
 
-fn find_harmonic_antinodes(p0: &Position, p1: &Position, map: &Map) -> Vec<Position> {
+// With `lattice`, step by the difference divided by its GCD: a pair 4 apart across and 2
+// down then also has antinodes 2 across and 1 down from each antenna
+fn find_harmonic_antinodes(
+    p0: &Position,
+    p1: &Position,
+    map: &Map,
+    lattice: bool,
+) -> Vec<Position> {
     let mut result = vec![p0.clone(), p1.clone()];
-    let diff = (p1.0 - p0.0, p1.1 - p0.1);
+    let mut diff = (p1.0 - p0.0, p1.1 - p0.1);
+    if lattice {
+        let divisor = gcd(diff.0, diff.1);
+        diff = (diff.0 / divisor, diff.1 / divisor);
+        // Points strictly between the pair
+        let mut temp = p0.clone();
+        loop {
+            temp.0 += diff.0;
+            temp.1 += diff.1;
+            if temp == *p1 {
+                break;
+            }
+            result.push(temp.clone());
+        }
+    }
 
     let mut temp = p0.clone();
     loop {
@@ -88,12 +222,112 @@ fn find_harmonic_antinodes(p0: &Position, p1: &Position, map: &Map) -> Vec<Posit
     result
 }
 
+fn parse_position(s: &str) -> Option<Position> {
+    let (x, y) = s.split_once(',')?;
+    Some(Position(x.parse().ok()?, y.parse().ok()?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    // Part 1: one antinode on either side of a pair
+    Simple,
+    // Part 2: whole steps of the pair's distance in both directions
+    Harmonic,
+    // Every grid point in line with the pair
+    Lattice,
+}
+
+// A pair of same frequency antennas producing an antinode
+#[derive(Debug, PartialEq, Eq)]
+struct Source {
+    frequency: char,
+    antennas: (Position, Position),
+}
+
+// Every antinode along with each pair of antennas that produces it
+struct Analysis {
+    sources: HashMap<Position, Vec<Source>>,
+}
+
+impl Analysis {
+    fn new(map: &Map, mode: Mode) -> Self {
+        let mut sources: HashMap<Position, Vec<Source>> = HashMap::new();
+        for (&frequency, v) in &map.antennas {
+            for pair in v.iter().combinations(2) {
+                let found = match mode {
+                    Mode::Simple => {
+                        let (antinode0, antinode1) = find_antinodes(pair[0], pair[1]);
+                        [antinode0, antinode1]
+                            .into_iter()
+                            .filter(|p| map.in_map(p))
+                            .collect()
+                    }
+                    Mode::Harmonic => find_harmonic_antinodes(pair[0], pair[1], map, false),
+                    Mode::Lattice => find_harmonic_antinodes(pair[0], pair[1], map, true),
+                };
+                for antinode in found {
+                    sources.entry(antinode).or_default().push(Source {
+                        frequency,
+                        antennas: (pair[0].clone(), pair[1].clone()),
+                    });
+                }
+            }
+        }
+
+        Self { sources }
+    }
+
+    fn count(&self) -> usize {
+        self.sources.len()
+    }
+
+    fn frequencies_at(&self, p: &Position) -> BTreeSet<char> {
+        self.sources
+            .get(p)
+            .into_iter()
+            .flatten()
+            .map(|s| s.frequency)
+            .collect()
+    }
+
+    // Antinodes produced by more than one pair, in reading order
+    fn overlaps(&self) -> Vec<(&Position, &Vec<Source>)> {
+        self.sources
+            .iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .sorted_by_key(|(p, _)| (p.1, p.0))
+            .collect()
+    }
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
 struct Map {
     x_size: usize,
     y_size: usize,
     antennas: HashMap<char, Vec<Position>>,
 }
 
+impl Clone for Map {
+    fn clone(&self) -> Self {
+        Self {
+            x_size: self.x_size,
+            y_size: self.y_size,
+            antennas: self
+                .antennas
+                .iter()
+                .map(|(&frequency, v)| (frequency, v.iter().map(|p| p.clone()).collect()))
+                .collect(),
+        }
+    }
+}
+
 impl Map {
     fn from_file(path: &str) -> std::io::Result<Self> {
         let f = File::open(path)?;
@@ -133,6 +367,48 @@ impl Map {
         })
     }
 
+    // Antennas by their frequency, antinodes without an antenna on them as `#`
+    fn render(&self, antinodes: &HashSet<Position>) -> String {
+        let antennas = self
+            .antennas
+            .iter()
+            .flat_map(|(&frequency, v)| v.iter().map(move |p| (p.clone(), frequency)))
+            .collect::<HashMap<_, _>>();
+
+        let mut result = String::new();
+        for y in 0..=self.y_size as i32 {
+            for x in 0..=self.x_size as i32 {
+                let p = Position(x, y);
+                let c = match antennas.get(&p) {
+                    Some(&frequency) => frequency,
+                    None if antinodes.contains(&p) => '#',
+                    None => '.',
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+        result.pop();
+
+        result
+    }
+
+    // The frequency of the antenna that was there, if there was one
+    fn without_antenna(&mut self, p: &Position) -> Option<char> {
+        let (&frequency, v) = self.antennas.iter_mut().find(|(_, v)| v.contains(p))?;
+        v.retain(|antenna| antenna != p);
+        if v.is_empty() {
+            self.antennas.remove(&frequency);
+        }
+
+        Some(frequency)
+    }
+
+    fn with_antenna(&mut self, frequency: char, p: Position) {
+        self.without_antenna(&p);
+        self.antennas.entry(frequency).or_default().push(p);
+    }
+
     fn in_map(&self, p: &Position) -> bool {
         if p.0 > self.x_size as i32 || p.1 > self.y_size as i32 {
             return false;
@@ -187,4 +463,81 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_lattice() -> std::io::Result<()> {
+        let map = Map::from_file("../test_input/day08test.txt")?;
+        assert_eq!(count_distinct(&harmonic_antinodes(&map, true)), 34);
+
+        let antinodes = find_harmonic_antinodes(&Position(0, 0), &Position(4, 2), &map, true);
+        assert!(antinodes.contains(&Position(2, 1)));
+        assert!(antinodes.contains(&Position(6, 3)));
+        assert_eq!(antinodes.len(), 6);
+        let antinodes = find_harmonic_antinodes(&Position(0, 0), &Position(4, 2), &map, false);
+        assert!(!antinodes.contains(&Position(2, 1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> std::io::Result<()> {
+        let map = Map::from_file("../test_input/day08test.txt")?;
+        let all = antinodes(&map)
+            .values()
+            .flatten()
+            .map(|p| p.clone())
+            .collect();
+        assert_eq!(
+            map.render(&all),
+            "......#....#\n\
+             ...#....0...\n\
+             ....#0....#.\n\
+             ..#....0....\n\
+             ....0....#..\n\
+             .#....A.....\n\
+             ...#........\n\
+             #......#....\n\
+             ........A...\n\
+             .........A..\n\
+             ..........#.\n\
+             ..........#."
+        );
+        assert_eq!(
+            frequency_report(&map, &antinodes(&map)),
+            "0: 4 antennas, 10 antinodes\nA: 3 antennas, 5 antinodes\nTotal: 14 antinodes\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_analysis() -> std::io::Result<()> {
+        let map = Map::from_file("../test_input/day08test.txt")?;
+        let analysis = Analysis::new(&map, Mode::Simple);
+        assert_eq!(analysis.count(), 14);
+        assert_eq!(Analysis::new(&map, Mode::Harmonic).count(), 34);
+        assert_eq!(Analysis::new(&map, Mode::Lattice).count(), 34);
+
+        // The one antinode both frequencies produce
+        let shared = analysis
+            .sources
+            .keys()
+            .filter(|p| analysis.frequencies_at(p).len() == 2)
+            .collect::<Vec<_>>();
+        assert_eq!(shared, vec![&Position(3, 1)]);
+        assert_eq!(analysis.overlaps().len(), 1);
+        assert_eq!(analysis.frequencies_at(&Position(0, 0)), BTreeSet::new());
+
+        // Removing one of the A antennas leaves a single pair
+        let mut changed = map.clone();
+        assert_eq!(changed.without_antenna(&Position(9, 9)), Some('A'));
+        assert_eq!(changed.without_antenna(&Position(9, 9)), None);
+        let changed_analysis = Analysis::new(&changed, Mode::Simple);
+        assert_eq!(changed_analysis.count(), 12);
+
+        changed.with_antenna('A', Position(9, 9));
+        assert_eq!(Analysis::new(&changed, Mode::Simple).count(), 14);
+
+        Ok(())
+    }
 }
@@ -1,12 +1,52 @@
-use std::cmp::max;
-use std::collections::LinkedList;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 fn main() -> std::io::Result<()> {
-    let f = File::open("input/day09.txt")?;
-    let reader = BufReader::new(f);
-    let input = reader.lines().nth(0).unwrap()?;
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // Compact a generated disk map of the given number of digits instead of the puzzle input
+    let input = match args.iter().position(|a| a == "--generate") {
+        Some(i) => {
+            let digits = args
+                .get(i + 1)
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "--generate expects a number of digits",
+                    )
+                })?;
+            generate_input(digits)
+        }
+        None => {
+            let f = File::open("input/day09.txt")?;
+            let reader = BufReader::new(f);
+            reader.lines().next().unwrap()?
+        }
+    };
+
+    let disk = parse_disk(&input);
+    let log = args.contains(&String::from("--moves"));
+    let render = args.contains(&String::from("--render"));
+    if render && disk.iter().sum::<usize>() > MAX_RENDER_BLOCKS {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("--render only works for disks of up to {MAX_RENDER_BLOCKS} blocks"),
+        ));
+    }
+
+    if log || render {
+        println!("Part1:");
+        trace(&disk, log, render, |on_move| {
+            compact_blocks(&disk, on_move);
+        });
+        println!("Part2:");
+        trace(&disk, log, render, |on_move| {
+            compact_files(&disk, on_move);
+        });
+    }
 
     let part1_start = std::time::Instant::now();
     let part1_result = part1(&input);
@@ -25,137 +65,266 @@ fn main() -> std::io::Result<()> {
         end.duration_since(part2_start)
     );
 
+    if args.contains(&String::from("--report")) {
+        let blocks = compact_blocks(&disk, &mut |_| {});
+        println!("Part1: {}", Fragmentation::new(&blocks));
+        let files = compact_files(&disk, &mut |_| {});
+        println!(
+            "Part2: {}",
+            Fragmentation::new(&layout(&files, disk.iter().sum()))
+        );
+    }
+
     Ok(())
 }
 
-fn part1(input: &str) -> usize {
-    let mut result = Vec::new();
-    let mut stack = LinkedList::new();
-    let mut to_fill = LinkedList::new();
-    let mut index = 0usize;
-    let mut file_num = 0usize;
+// Rendering one line per move stops being readable well before this
+const MAX_RENDER_BLOCKS: usize = 1000;
 
-    for (i, c) in input.chars().enumerate() {
-        for _ in 0..c.to_digit(10).unwrap() {
-            if i % 2 == 0 {
-                result.push(Some(file_num));
-                stack.push_front((file_num, index));
-            } else {
-                result.push(None);
-                to_fill.push_back(index);
-            }
-            index += 1;
+// Print the moves a compaction makes and/or the layout after each of them
+fn trace(disk: &[usize], log: bool, render: bool, compact: impl FnOnce(&mut dyn FnMut(&Move))) {
+    let mut layout = expand(disk);
+    if render {
+        println!("{}", render_layout(&layout));
+    }
+    compact(&mut |m| {
+        if log {
+            println!("{m}");
         }
-        if i % 2 == 0 {
-            file_num += 1;
+        if render {
+            m.apply(&mut layout);
+            println!("{}", render_layout(&layout));
         }
+    });
+}
+
+fn parse_disk(input: &str) -> Vec<usize> {
+    input
+        .trim()
+        .chars()
+        .map(|c| c.to_digit(10).unwrap() as usize)
+        .collect()
+}
+
+// One entry per block, holding the ID of the file stored there
+fn expand(disk: &[usize]) -> Vec<Option<usize>> {
+    let mut result = Vec::with_capacity(disk.iter().sum());
+    for (i, &len) in disk.iter().enumerate() {
+        let id = if i % 2 == 0 { Some(i / 2) } else { None };
+        result.extend(std::iter::repeat_n(id, len));
     }
+    result
+}
 
-    for i in to_fill.into_iter() {
-        if is_compact(&result) {
+fn part1(input: &str) -> u128 {
+    let blocks = compact_blocks(&parse_disk(input), &mut |_| {});
+    checksum(
+        blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, id)| id.map(|id| (i, id))),
+    )
+}
+
+// Moves single blocks, handing each move to `on_move`, and returns the final layout
+fn compact_blocks(disk: &[usize], on_move: &mut dyn FnMut(&Move)) -> Vec<Option<usize>> {
+    let mut result = expand(disk);
+
+    // Move the last file block into the first free block until the two meet
+    let (mut free, mut last) = (0, result.len());
+    loop {
+        while free < result.len() && result[free].is_some() {
+            free += 1;
+        }
+        while last > 0 && result[last - 1].is_none() {
+            last -= 1;
+        }
+        if last == 0 || free >= last - 1 {
             break;
         }
-        let (val, index) = stack.pop_front().unwrap();
-        result.remove(index);
-        result[i] = Some(val);
+        result.swap(free, last - 1);
+        on_move(&Move {
+            id: result[free].unwrap(),
+            from: last - 1,
+            to: free,
+            len: 1,
+        });
     }
 
-    checksum(&result)
+    result
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Block {
     id: usize,
     start: usize,
     len: usize,
 }
 
-impl Block {
-    pub(crate) fn clone(&self) -> Self {
-        Self {
-            id: self.id,
-            start: self.start,
-            len: self.len,
-        }
-    }
+fn part2(input: &str) -> u128 {
+    let files = compact_files(&parse_disk(input), &mut |_| {});
+    checksum(
+        files
+            .iter()
+            .flat_map(|file| (file.start..file.start + file.len).map(|i| (i, file.id))),
+    )
 }
 
-fn part2(input: &str) -> usize {
-    let mut stack = LinkedList::new();
-    let mut free_list = LinkedList::new(); // this is faster as a Vec!
+// Moves whole files, handing each move to `on_move`, and returns where every file ended up
+fn compact_files(disk: &[usize], on_move: &mut dyn FnMut(&Move)) -> Vec<Block> {
+    let mut files = Vec::new();
+    // Starts of the free spans of each length, smallest first
+    let mut free_spans = vec![BinaryHeap::new(); 10];
 
     let mut index = 0usize;
-    let mut max_index = 0usize;
-    let mut file_id = 0usize;
-
-    for (i, c) in input.chars().enumerate() {
-        let len = c.to_digit(10).unwrap() as usize;
+    for (i, &len) in disk.iter().enumerate() {
         if i % 2 == 0 {
-            stack.push_front(Block {
-                id: file_id,
-                start: index,
-                len,
-            });
-            file_id += 1;
-        } else {
-            free_list.push_back(Block {
-                id: 0,
+            files.push(Block {
+                id: i / 2,
                 start: index,
                 len,
             });
+        } else if len > 0 {
+            free_spans[len].push(Reverse(index));
         }
         index += len;
-        max_index = max(index, max_index);
-    }
-
-    let mut blocks = LinkedList::new();
-
-    for block in stack.iter() {
-        let mut moved = false;
-        for free_block in free_list.iter_mut() {
-            // Make sure not to move any blocks to the right!
-            if free_block.start < block.start && free_block.len >= block.len {
-                blocks.push_back(Block {
-                    id: block.id,
-                    start: free_block.start,
-                    len: block.len,
-                });
-                free_block.len -= block.len;
-                free_block.start += block.len;
-                moved = true;
-                break;
-            }
-        }
-        if !moved {
-            blocks.push_back(block.clone());
-        }
     }
 
-    let mut result = vec![None; max_index + 1];
-    for block in blocks.iter() {
-        for i in block.start..(block.start + block.len) {
-            result[i] = Some(block.id);
+    for file in files.iter_mut().rev() {
+        // The leftmost span big enough for the file, which has to be left of it
+        let Some((span_len, Reverse(start))) = (file.len..free_spans.len())
+            .filter_map(|len| free_spans[len].peek().map(|&start| (len, start)))
+            .filter(|&(_, Reverse(start))| start < file.start)
+            .min_by_key(|&(_, Reverse(start))| start)
+        else {
+            continue;
+        };
+
+        free_spans[span_len].pop();
+        on_move(&Move {
+            id: file.id,
+            from: file.start,
+            to: start,
+            len: file.len,
+        });
+        file.start = start;
+        if span_len > file.len {
+            free_spans[span_len - file.len].push(Reverse(start + file.len));
         }
     }
 
-    checksum(&result[..])
+    files
+}
+
+// The checksum grows with the square of the disk size, so millions of digits overflow u64
+fn checksum(blocks: impl IntoIterator<Item = (usize, usize)>) -> u128 {
+    blocks
+        .into_iter()
+        .map(|(i, id)| i as u128 * id as u128)
+        .sum()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Move {
+    id: usize,
+    from: usize,
+    to: usize,
+    len: usize,
+}
+
+impl Move {
+    fn apply(&self, layout: &mut [Option<usize>]) {
+        layout[self.from..self.from + self.len].fill(None);
+        layout[self.to..self.to + self.len].fill(Some(self.id));
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "file {} x{}: {} -> {}",
+            self.id, self.len, self.from, self.to
+        )
+    }
+}
+
+fn layout(files: &[Block], size: usize) -> Vec<Option<usize>> {
+    let mut result = vec![None; size];
+    for file in files {
+        result[file.start..file.start + file.len].fill(Some(file.id));
+    }
+    result
+}
+
+// `00...111` like the puzzle while IDs are single digits, otherwise every block gets a
+// column wide enough for the largest ID
+fn render_layout(layout: &[Option<usize>]) -> String {
+    let max_id = layout.iter().flatten().max().copied().unwrap_or(0);
+    let width = max_id.to_string().len();
+    let cells = layout.iter().map(|block| match block {
+        Some(id) => format!("{id:>width$}"),
+        None => format!("{:>width$}", "."),
+    });
+
+    if width == 1 {
+        cells.collect()
+    } else {
+        cells.collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fragmentation {
+    free_blocks: usize,
+    // Free spans between files, not counting the free space after the last file
+    gaps: usize,
+    largest_gap: usize,
 }
 
-fn checksum(v: &[Option<usize>]) -> usize {
-    let mut checksum = 0;
-    for (i, n) in v.iter().enumerate() {
-        match n {
-            Some(v) => checksum += i * v,
-            None => {}
+impl Fragmentation {
+    fn new(layout: &[Option<usize>]) -> Self {
+        let used = layout
+            .iter()
+            .rposition(|block| block.is_some())
+            .map_or(0, |i| i + 1);
+        let spans = layout[..used]
+            .split(|block| block.is_some())
+            .map(|span| span.len())
+            .filter(|&len| len > 0)
+            .collect::<Vec<_>>();
+
+        Self {
+            free_blocks: layout.iter().filter(|block| block.is_none()).count(),
+            gaps: spans.len(),
+            largest_gap: spans.iter().max().copied().unwrap_or(0),
         }
     }
-    checksum
 }
 
-fn is_compact(v: &Vec<Option<usize>>) -> bool {
-    let total = v.iter().filter(|v| v.is_some()).count();
+impl std::fmt::Display for Fragmentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} free blocks, {} gaps between files, largest gap {}",
+            self.free_blocks, self.gaps, self.largest_gap
+        )
+    }
+}
 
-    v[0..total].iter().all(|v| v.is_some())
+fn generate_input(digits: usize) -> String {
+    let mut random = compare::Xorshift::new();
+    (0..digits)
+        .map(|i| {
+            // Files are never empty
+            let digit = if i % 2 == 0 {
+                1 + random.below(9)
+            } else {
+                random.below(10)
+            };
+            char::from_digit(digit as u32, 10).unwrap()
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -166,7 +335,7 @@ mod tests {
     fn test_part1() -> std::io::Result<()> {
         let f = File::open("../test_input/day09test.txt")?;
         let reader = BufReader::new(f);
-        let input = reader.lines().nth(0).unwrap()?;
+        let input = reader.lines().next().unwrap()?;
         assert_eq!(part1(&input), 1928);
 
         Ok(())
@@ -176,9 +345,75 @@ mod tests {
     fn test_part2() -> std::io::Result<()> {
         let f = File::open("../test_input/day09test.txt")?;
         let reader = BufReader::new(f);
-        let input = reader.lines().nth(0).unwrap()?;
+        let input = reader.lines().next().unwrap()?;
         assert_eq!(part2(&input), 2858);
 
         Ok(())
     }
+
+    #[test]
+    fn test_edge_cases() {
+        // 0..111....22222 compacts to 022111222
+        assert_eq!(part1("12345"), 60);
+        assert_eq!(part2("12345"), 132);
+        assert_eq!(part1("1"), 0);
+        assert_eq!(part1("90909"), 513);
+        assert_eq!(part2("90909"), 513);
+    }
+
+    #[test]
+    fn test_moves() -> std::io::Result<()> {
+        let f = File::open("../test_input/day09test.txt")?;
+        let reader = BufReader::new(f);
+        let disk = parse_disk(&reader.lines().next().unwrap()?);
+
+        let mut layout = expand(&disk);
+        let mut moves = Vec::new();
+        let files = compact_files(&disk, &mut |m| {
+            m.apply(&mut layout);
+            moves.push(m.clone());
+        });
+        assert_eq!(
+            moves[0],
+            Move {
+                id: 9,
+                from: 40,
+                to: 2,
+                len: 2
+            }
+        );
+        assert_eq!(moves.len(), 4);
+        assert_eq!(
+            render_layout(&layout),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(layout, self::layout(&files, layout.len()));
+        assert_eq!(
+            Fragmentation::new(&layout),
+            Fragmentation {
+                free_blocks: 14,
+                gaps: 5,
+                largest_gap: 5
+            }
+        );
+
+        let mut layout = expand(&disk);
+        let blocks = compact_blocks(&disk, &mut |m| m.apply(&mut layout));
+        assert_eq!(layout, blocks);
+        assert_eq!(
+            render_layout(&layout),
+            "0099811188827773336446555566.............."
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_wide_ids() {
+        let layout = expand(&parse_disk("1010101010101010101010121"));
+        assert_eq!(
+            render_layout(&layout),
+            " 0  1  2  3  4  5  6  7  8  9 10 11  .  . 12"
+        );
+    }
 }
@@ -1,38 +1,90 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+// Stepping the robots a second at a time vs jumping straight to each second. Part 2 looks
+// for the first frame where no two robots overlap, which is where the tree shows up.
+const STRATEGIES: [compare::Strategy<Map>; 4] = [
+    compare::Strategy {
+        part: 1,
+        name: "step by step",
+        run: |map| {
+            let mut map = map.clone();
+            map.run_n_seconds(100);
+            Some(map.safety_factor().to_string())
+        },
+    },
+    compare::Strategy {
+        part: 1,
+        name: "closed form",
+        run: |map| {
+            let mut map = map.clone();
+            map.jump_n_seconds(100);
+            Some(map.safety_factor().to_string())
+        },
+    },
+    compare::Strategy {
+        part: 2,
+        name: "step by step",
+        run: |map| Some(answer(part2(map, Map::no_overlaps))),
+    },
+    compare::Strategy {
+        part: 2,
+        name: "closed form",
+        run: |map| Some(answer(part2_closed_form(map))),
+    },
+];
+
 fn main() -> std::io::Result<()> {
-    let mut map1 = Map::from_file(101, 103, "input/day14.txt")?;
-    let mut map2 = Map::from_file(101, 103, "input/day14.txt")?;
+    let args = std::env::args().collect::<Vec<_>>();
+    let map = Map::from_file(101, 103, "input/day14.txt")?;
+    if repl::start(RobotSimulation::new(&map), &args)? {
+        return Ok(());
+    }
+    if compare::start(&map, &STRATEGIES, &args) {
+        return Ok(());
+    }
 
+    let mut map1 = map.clone();
     map1.run_n_seconds(100);
-    let part1: usize = [0usize, 1usize, 2usize, 3usize]
-        .iter()
-        .map(|&quadrant| map1.robots_in_quadrant(quadrant))
-        .product();
-
-    println!("part1: {}", part1);
-
-    let mut part2 = 0;
-    // Part2 -- check for symmetry?
-    // while map2.robots_in_quadrant(0) != map2.robots_in_quadrant(1)
-    //     || map2.robots_in_quadrant(2) != map2.robots_in_quadrant(3)
-    //     || !map2.no_overlaps() {
-    //     map2.run_one_second();
-    //     part2 += 1;
-    // }
-    // nope....check for uniqueness? yep...
-    while !map2.no_overlaps() {
-        map2.run_one_second();
-        part2 += 1;
-    }
+    println!("part1: {}", map1.safety_factor());
 
+    let part2 = part2(&map, Map::no_overlaps).expect("robots never stopped overlapping");
     println!("part2: {}", part2);
 
     Ok(())
 }
 
-#[derive(Debug)]
+// Seconds until `done` holds, giving up once every robot is back where it started
+fn part2(map: &Map, done: fn(&Map) -> bool) -> Option<usize> {
+    let mut map = map.clone();
+    for seconds in 0..(map.x_size * map.y_size) {
+        if done(&map) {
+            return Some(seconds);
+        }
+        map.run_one_second();
+    }
+
+    None
+}
+
+// Jump each candidate second straight from the start, checking for overlaps with a set
+fn part2_closed_form(map: &Map) -> Option<usize> {
+    (0..(map.x_size * map.y_size)).find(|&seconds| {
+        let mut map = map.clone();
+        map.jump_n_seconds(seconds);
+        let mut seen = HashSet::new();
+        map.robots
+            .iter()
+            .all(|robot| seen.insert((robot.x_pos, robot.y_pos)))
+    })
+}
+
+fn answer(seconds: Option<usize>) -> String {
+    seconds.map_or(String::from("none"), |s| s.to_string())
+}
+
+#[derive(Debug, Clone)]
 struct Robot {
     x_pos: i32,
     y_pos: i32,
@@ -40,7 +92,7 @@ struct Robot {
     y_velocity: i32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Map {
     x_size: usize,
     y_size: usize,
@@ -75,8 +127,8 @@ impl Map {
 
             let x_pos = nums[0];
             let y_pos = nums[1];
-            let x_velocity = nums[2] as i32;
-            let y_velocity = nums[3] as i32;
+            let x_velocity = nums[2];
+            let y_velocity = nums[3];
 
             robots.push(Robot {
                 x_pos,
@@ -99,6 +151,15 @@ impl Map {
         }
     }
 
+    fn jump_n_seconds(&mut self, n: usize) {
+        for robot in &mut self.robots {
+            robot.x_pos =
+                (robot.x_pos + robot.x_velocity * n as i32).rem_euclid(self.x_size as i32);
+            robot.y_pos =
+                (robot.y_pos + robot.y_velocity * n as i32).rem_euclid(self.y_size as i32);
+        }
+    }
+
     fn run_one_second(&mut self) {
         for robot in &mut self.robots {
             let mut new_x_pos = robot.x_pos + robot.x_velocity;
@@ -146,26 +207,40 @@ impl Map {
             .count()
     }
 
+    fn safety_factor(&self) -> usize {
+        (0..4)
+            .map(|quadrant| self.robots_in_quadrant(quadrant))
+            .product()
+    }
+
     #[allow(dead_code)]
     fn display(&self, quadtrants: bool) {
+        println!("{}", self.render(quadtrants));
+    }
+
+    fn render(&self, quadtrants: bool) -> String {
+        let mut result = String::new();
         for y in 0..self.y_size {
             if quadtrants && y == self.y_size / 2 {
-                println!();
+                result.push('\n');
                 continue;
             }
             for x in 0..self.x_size {
                 if quadtrants && x == self.x_size / 2 {
-                    print!(" ");
+                    result.push(' ');
                     continue;
                 }
 
                 match self.robots_at(x, y) {
-                    0 => print!("."),
-                    n @ _ => print!("{n}"),
+                    0 => result.push('.'),
+                    n => result.push_str(&n.to_string()),
                 }
             }
-            print!("\n");
+            result.push('\n');
         }
+        result.pop();
+
+        result
     }
 
     fn no_overlaps(&self) -> bool {
@@ -187,3 +262,86 @@ impl Map {
             .count()
     }
 }
+
+struct RobotSimulation {
+    initial: Map,
+    map: Map,
+}
+
+impl RobotSimulation {
+    fn new(map: &Map) -> Self {
+        Self {
+            initial: map.clone(),
+            map: map.clone(),
+        }
+    }
+}
+
+impl repl::Simulation for RobotSimulation {
+    fn step(&mut self) -> bool {
+        self.map.run_one_second();
+        true
+    }
+
+    fn show(&self) -> String {
+        self.map.render(false)
+    }
+
+    fn query(&self, args: &[&str]) -> Option<String> {
+        match args {
+            ["quadrants"] => {
+                let counts = (0..4)
+                    .map(|quadrant| self.map.robots_in_quadrant(quadrant))
+                    .collect::<Vec<_>>();
+                Some(format!(
+                    "{:?}, safety factor: {}",
+                    counts,
+                    self.map.safety_factor()
+                ))
+            }
+            ["overlaps"] => Some((!self.map.no_overlaps()).to_string()),
+            [x, y] => Some(
+                self.map
+                    .robots_at(x.parse().ok()?, y.parse().ok()?)
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+
+    fn queries(&self) -> &'static str {
+        "  quadrants  robots in each quadrant and their product\n  overlaps   whether any robots share a tile\n  <x> <y>    number of robots on a tile"
+    }
+
+    fn reset(&mut self) {
+        self.map = self.initial.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part1() -> std::io::Result<()> {
+        let map = Map::from_file(11, 7, "../test_input/day14test.txt")?;
+        for outcome in compare::compare(&map, &STRATEGIES[..2]) {
+            assert_eq!(outcome.answer.as_deref(), Some("12"), "{}", outcome.name);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> std::io::Result<()> {
+        let map = Map::from_file(11, 7, "../test_input/day14test.txt")?;
+        let outcomes = compare::compare(&map, &STRATEGIES[2..]);
+        assert!(compare::disagreements(&outcomes).is_empty());
+        assert_eq!(
+            outcomes[0].answer,
+            part2(&map, Map::no_overlaps).map(|s| s.to_string())
+        );
+
+        Ok(())
+    }
+}
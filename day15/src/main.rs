@@ -5,6 +5,7 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let f = File::open("input/day15.txt")?;
     let reader = BufReader::new(f);
     let mut lines = reader.lines();
@@ -26,6 +27,15 @@ fn main() -> std::io::Result<()> {
         moves += &n;
     }
 
+    if args.contains(&String::from("--repl")) {
+        let mut map = map.clone();
+        if args.contains(&String::from("--part2")) {
+            map.part2ify();
+        }
+        repl::start(WarehouseSimulation::new(&map, &moves), &args)?;
+        return Ok(());
+    }
+
     for dir in moves.chars() {
         if dir.is_whitespace() {
             continue;
@@ -115,7 +125,7 @@ impl Map {
     }
 
     fn is_wall(&self, pos: &Position) -> bool {
-        if !self.is_valid(&pos) {
+        if !self.is_valid(pos) {
             panic!();
         }
 
@@ -160,7 +170,7 @@ impl Map {
 
     fn can_move_box(&self, b: &Position, dir: char) -> bool {
         // println!("Checking: {:?}", b);
-        let orig_b = b.clone();
+        let orig_b = *b;
         let b = if self.doublewide && self.is_box(b) && !self.boxes.contains(b) {
             Position { x: b.x - 1, y: b.y }
         } else {
@@ -188,7 +198,7 @@ impl Map {
         let right_side = if self.doublewide {
             Position { x: b.x + 1, y: b.y }
         } else {
-            b.clone()
+            b
         };
         if self.is_box(&next_pos) && next_pos != right_side && !self.can_move_box(&next_pos, dir) {
             return false;
@@ -206,7 +216,7 @@ impl Map {
     }
 
     fn move_box(&mut self, b: &Position, dir: char) -> bool {
-        let orig_b = b.clone();
+        let orig_b = *b;
         let b = if self.doublewide && self.is_box(b) && !self.boxes.contains(b) {
             Position { x: b.x - 1, y: b.y }
         } else {
@@ -230,7 +240,7 @@ impl Map {
         let right_side = if self.doublewide {
             Position { x: b.x + 1, y: b.y }
         } else {
-            b.clone()
+            b
         };
         if (self.is_box(&b) && !self.can_move_box(&b, dir))
             || (self.doublewide && self.is_box(&right_side) && !self.can_move_box(&b, dir))
@@ -268,7 +278,7 @@ impl Map {
     }
 
     fn part2ify(&mut self) {
-        self.x_size = self.x_size * 2;
+        self.x_size *= 2;
         self.robot = Position {
             x: self.robot.x * 2,
             y: self.robot.y,
@@ -294,6 +304,11 @@ impl Map {
 
     #[allow(dead_code)]
     fn display(&self) {
+        println!("{}", self.render());
+    }
+
+    fn render(&self) -> String {
+        let mut result = String::new();
         for y in 0..self.y_size {
             for x in 0..self.x_size {
                 let p = Position {
@@ -301,22 +316,84 @@ impl Map {
                     y: y as i32,
                 };
                 if self.robot == p {
-                    print!("@");
+                    result.push('@');
                     continue;
                 }
                 if self.is_wall(&p) {
-                    print!("#");
+                    result.push('#');
                     continue;
                 }
                 if self.is_box(&p) {
-                    print!("O");
+                    result.push('O');
                     continue;
                 }
-                print!(".");
+                result.push('.');
             }
-            println!();
+            result.push('\n');
+        }
+        result.pop();
+
+        result
+    }
+}
+
+struct WarehouseSimulation {
+    initial: Map,
+    map: Map,
+    moves: Vec<char>,
+    next_move: usize,
+}
+
+impl WarehouseSimulation {
+    fn new(map: &Map, moves: &str) -> Self {
+        Self {
+            initial: map.clone(),
+            map: map.clone(),
+            moves: moves.chars().filter(|c| !c.is_whitespace()).collect(),
+            next_move: 0,
+        }
+    }
+}
+
+impl repl::Simulation for WarehouseSimulation {
+    fn step(&mut self) -> bool {
+        let Some(&dir) = self.moves.get(self.next_move) else {
+            return false;
+        };
+        self.map.move_robot(dir);
+        self.next_move += 1;
+        true
+    }
+
+    fn show(&self) -> String {
+        self.map.render()
+    }
+
+    fn query(&self, args: &[&str]) -> Option<String> {
+        match args {
+            ["gps"] => Some(self.map.gps_sum().to_string()),
+            ["robot"] => Some(format!("({}, {})", self.map.robot.x, self.map.robot.y)),
+            ["moves"] => Some(format!(
+                "{} of {} done, next: {}",
+                self.next_move,
+                self.moves.len(),
+                self.moves[self.next_move..]
+                    .iter()
+                    .take(10)
+                    .collect::<String>()
+            )),
+            _ => None,
         }
     }
+
+    fn queries(&self) -> &'static str {
+        "  gps        sum of the boxes' GPS coordinates\n  robot      position of the robot\n  moves      progress through the move list"
+    }
+
+    fn reset(&mut self) {
+        self.map = self.initial.clone();
+        self.next_move = 0;
+    }
 }
 
 #[allow(dead_code)]
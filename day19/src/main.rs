@@ -3,11 +3,63 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::time::Instant;
 
+type Input = (Vec<String>, Vec<String>);
+
+// Plain recursion vs the memoised searcher, which also counts the arrangements for part 2
+const STRATEGIES: [compare::Strategy<Input>; 3] = [
+    compare::Strategy {
+        part: 1,
+        name: "recursive",
+        run: |(towels, patterns)| {
+            Some(
+                patterns
+                    .iter()
+                    .filter(|p| is_possible(p, towels))
+                    .count()
+                    .to_string(),
+            )
+        },
+    },
+    compare::Strategy {
+        part: 1,
+        name: "cached searcher",
+        run: |(towels, patterns)| {
+            let mut cached_searcher = Searcher::new();
+            Some(
+                patterns
+                    .iter()
+                    .filter(|p| cached_searcher.possibilities(p, towels) > 0)
+                    .count()
+                    .to_string(),
+            )
+        },
+    },
+    compare::Strategy {
+        part: 2,
+        name: "cached searcher",
+        run: |(towels, patterns)| {
+            let mut cached_searcher = Searcher::new();
+            Some(
+                patterns
+                    .iter()
+                    .map(|p| cached_searcher.possibilities(p, towels))
+                    .sum::<usize>()
+                    .to_string(),
+            )
+        },
+    },
+];
+
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
     let f = File::open("input/day19.txt")?;
     let reader = BufReader::new(f);
 
-    let (towels, patterns) = get_towels_and_patterns(reader);
+    let input = get_towels_and_patterns(reader);
+    if compare::start(&input, &STRATEGIES, &args) {
+        return Ok(());
+    }
+    let (towels, patterns) = input;
 
     let start = Instant::now();
     let part1 = patterns.iter().filter(|p| is_possible(p, &towels)).count();
@@ -42,7 +94,7 @@ fn get_towels_and_patterns(reader: BufReader<File>) -> (Vec<String>, Vec<String>
         .unwrap()
         .unwrap()
         .split(", ")
-        .map(|s| String::from(s))
+        .map(String::from)
         .collect::<Vec<_>>();
 
     let mut patterns = Vec::new();
@@ -69,7 +121,7 @@ fn is_possible(pattern: &str, towels: &Vec<String>) -> bool {
         match pattern.find(towel) {
             Some(0) => {
                 let substring = &pattern[towel.chars().count()..];
-                if is_possible(substring, &towels) {
+                if is_possible(substring, towels) {
                     return true;
                 }
             }
@@ -152,4 +204,16 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_strategies_agree() -> std::io::Result<()> {
+        let f = File::open("../test_input/day19test.txt")?;
+        let reader = BufReader::new(f);
+        let input = get_towels_and_patterns(reader);
+
+        let outcomes = compare::compare(&input, &STRATEGIES);
+        assert!(compare::disagreements(&outcomes).is_empty());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,228 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+// A puzzle whose state can be advanced and inspected one step at a time
+pub trait Simulation {
+    // Advance by one step, returning false once there is nothing left to do
+    fn step(&mut self) -> bool;
+    fn show(&self) -> String;
+    // None if the query isn't recognised
+    fn query(&self, args: &[&str]) -> Option<String>;
+    // Help text listing the supported queries
+    fn queries(&self) -> &'static str;
+    fn reset(&mut self);
+}
+
+pub struct Repl<S: Simulation> {
+    sim: S,
+    steps: usize,
+    history: Vec<String>,
+}
+
+impl<S: Simulation> Repl<S> {
+    pub fn new(sim: S) -> Self {
+        Self {
+            sim,
+            steps: 0,
+            history: Vec::new(),
+        }
+    }
+
+    // Run the commands in `script` (if any) followed by whatever is on stdin
+    pub fn run(&mut self, script: Option<&Path>) -> std::io::Result<()> {
+        if let Some(script) = script {
+            let reader = BufReader::new(File::open(script)?);
+            for line in reader.lines() {
+                let line = line?;
+                println!("> {}", line);
+                if !self.handle(&line) {
+                    return Ok(());
+                }
+            }
+        }
+
+        let stdin = std::io::stdin();
+        let interactive = stdin.is_terminal();
+        let mut lines = stdin.lock().lines();
+        loop {
+            if interactive {
+                print!("> ");
+                std::io::stdout().flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            if !self.handle(&line?) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, line: &str) -> bool {
+        match self.execute(line) {
+            Some(output) => {
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Returns the command's output, or None when the session should end
+    pub fn execute(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        // Blank lines and comments are allowed so that scripts can be annotated
+        if line.is_empty() || line.starts_with('#') {
+            return Some(String::new());
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let output = match tokens[0] {
+            "step" => match tokens.get(1).map(|n| n.parse::<usize>()) {
+                None => self.step(1),
+                Some(Ok(n)) => self.step(n),
+                Some(Err(_)) => format!("Invalid step count: {}", tokens[1]),
+            },
+            "show" => self.sim.show(),
+            "query" => self
+                .sim
+                .query(&tokens[1..])
+                .unwrap_or_else(|| format!("Unknown query\n{}", self.sim.queries())),
+            "reset" => {
+                self.sim.reset();
+                self.steps = 0;
+                String::from("Reset to initial state")
+            }
+            "history" => self.history.join("\n"),
+            "save" => match tokens.get(1) {
+                Some(path) => match self.save(PathBuf::from(path).as_path()) {
+                    Ok(()) => format!("Saved {} commands to {}", self.history.len(), path),
+                    Err(e) => format!("Failed to save history: {}", e),
+                },
+                None => String::from("Usage: save <file>"),
+            },
+            "help" => self.help(),
+            "quit" | "exit" => return None,
+            _ => format!("Unknown command: {}\n{}", tokens[0], self.help()),
+        };
+        if !matches!(tokens[0], "history" | "save") {
+            self.history.push(line.to_string());
+        }
+
+        Some(output)
+    }
+
+    fn step(&mut self, n: usize) -> String {
+        for _ in 0..n {
+            if !self.sim.step() {
+                return format!("Finished after {} steps", self.steps);
+            }
+            self.steps += 1;
+        }
+
+        format!("Step {}", self.steps)
+    }
+
+    // Write the session history so it can be replayed with `--script`
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut contents = self.history.join("\n");
+        contents.push('\n');
+        fs::write(path, contents)
+    }
+
+    fn help(&self) -> String {
+        format!(
+            "Commands:\n  step [N]\n  show\n  query <args>\n  reset\n  history\n  save <file>\n  quit\nQueries:\n{}",
+            self.sim.queries()
+        )
+    }
+}
+
+// Start a session if `--repl` was passed on the command line, using `--script <file>` if given.
+// Returns false if no session was requested.
+pub fn start<S: Simulation>(sim: S, args: &[String]) -> std::io::Result<bool> {
+    if !args.iter().any(|a| a == "--repl") {
+        return Ok(false);
+    }
+    let script = args
+        .iter()
+        .position(|a| a == "--script")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from);
+
+    Repl::new(sim).run(script.as_deref())?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter {
+        value: usize,
+        limit: usize,
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) -> bool {
+            if self.value == self.limit {
+                return false;
+            }
+            self.value += 1;
+            true
+        }
+
+        fn show(&self) -> String {
+            self.value.to_string()
+        }
+
+        fn query(&self, args: &[&str]) -> Option<String> {
+            match args {
+                ["remaining"] => Some((self.limit - self.value).to_string()),
+                _ => None,
+            }
+        }
+
+        fn queries(&self) -> &'static str {
+            "  remaining"
+        }
+
+        fn reset(&mut self) {
+            self.value = 0;
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut repl = Repl::new(Counter { value: 0, limit: 5 });
+        assert_eq!(repl.execute("step").unwrap(), "Step 1");
+        assert_eq!(repl.execute("step 3").unwrap(), "Step 4");
+        assert_eq!(repl.execute("show").unwrap(), "4");
+        assert_eq!(repl.execute("query remaining").unwrap(), "1");
+        assert!(repl
+            .execute("query nope")
+            .unwrap()
+            .starts_with("Unknown query"));
+        assert_eq!(repl.execute("step 10").unwrap(), "Finished after 5 steps");
+        assert_eq!(repl.execute("reset").unwrap(), "Reset to initial state");
+        assert_eq!(repl.execute("show").unwrap(), "0");
+        assert_eq!(repl.execute("# comment").unwrap(), "");
+        assert!(repl.execute("quit").is_none());
+    }
+
+    #[test]
+    fn test_history() {
+        let mut repl = Repl::new(Counter { value: 0, limit: 5 });
+        repl.execute("step 2");
+        repl.execute("");
+        repl.execute("show");
+        repl.execute("history");
+        assert_eq!(repl.execute("history").unwrap(), "step 2\nshow");
+    }
+}
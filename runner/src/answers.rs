@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+// What an answers file holds for a part that hasn't been solved yet
+pub const UNKNOWN: &str = "?";
+
+// Expected answers for a puzzle, stored as one `<part>: <answer>` line per part
+#[derive(Debug, PartialEq, Eq)]
+pub struct Answers(BTreeMap<u8, String>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: u8,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "Part {}: expected {}, got {}",
+                self.part, self.expected, actual
+            ),
+            None => write!(
+                f,
+                "Part {}: expected {}, got nothing",
+                self.part, self.expected
+            ),
+        }
+    }
+}
+
+impl Answers {
+    pub fn parse(contents: &str) -> std::io::Result<Self> {
+        let mut answers = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: expected `<part>: <answer>`", i + 1),
+                )
+            };
+            let (part, answer) = line.split_once(':').ok_or_else(invalid)?;
+            let part = part.trim().parse().map_err(|_| invalid())?;
+            answers.insert(part, answer.trim().to_string());
+        }
+
+        Ok(Self(answers))
+    }
+
+    // None if nothing has been recorded for the puzzle
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::parse(&fs::read_to_string(path)?).map(Some)
+    }
+
+    // Compare against the `Part 1: ...` lines a day binary prints. Parts still marked as
+    // unknown are skipped.
+    pub fn check(&self, output: &str) -> Vec<Mismatch> {
+        self.0
+            .iter()
+            .filter(|(_, expected)| expected.as_str() != UNKNOWN)
+            .filter_map(|(&part, expected)| {
+                let actual = find_answer(output, part);
+                (actual.as_ref() != Some(expected)).then(|| Mismatch {
+                    part,
+                    expected: expected.clone(),
+                    actual,
+                })
+            })
+            .collect()
+    }
+}
+
+// Days label their answers in a few ways, e.g. `Part 1: 143`, `part1: 143`, `Part1 sum: 143`
+// or `Part1 = 143, duration: 2ms`, so anything between the part number and the `:` or `=`
+// is skipped, as is anything after a `, `
+fn find_answer(output: &str, part: u8) -> Option<String> {
+    output.lines().find_map(|line| {
+        let line = line.trim();
+        let label = line.get(..4)?;
+        if !label.eq_ignore_ascii_case("part") {
+            return None;
+        }
+        let rest = line[4..].trim_start().strip_prefix(&part.to_string())?;
+        // Part 1 isn't part 12
+        if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let (_, answer) = rest.split_once([':', '='])?;
+        let answer = answer.split(", ").next().unwrap_or_default().trim();
+        Some(answer.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() -> std::io::Result<()> {
+        let answers = Answers::parse("1: 4,6,3\n2: ?\n")?;
+        assert!(answers.check("Part1: 4,6,3\nPart2: 117440\n").is_empty());
+        assert_eq!(
+            answers.check("Part 1: 4,6,3,5, duration: 3ms\n"),
+            vec![Mismatch {
+                part: 1,
+                expected: String::from("4,6,3"),
+                actual: Some(String::from("4,6,3,5"))
+            }]
+        );
+
+        let answers = Answers::parse("1: 143\n\n2: 123")?;
+        let mismatches = answers.check("Part 1: 143\n");
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "Part 2: expected 123, got nothing"
+        );
+
+        assert!(Answers::parse("143\n").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_day_formats() -> std::io::Result<()> {
+        let answers = Answers::parse("1: 11\n2: 31\n")?;
+        for output in [
+            // day03, day05, day10, ...
+            "Part 1: 11\nPart 2: 31\n",
+            // day04, day06, day08, ...
+            "Part1: 11\nPart2: 31\n",
+            // day01
+            "Part1 sum: 11\nPart2 sum: 31\n",
+            // day02, day14
+            "part1: 11\npart2: 31\n",
+            // day07
+            "Part1 = 11, duration: 1.2ms\nPart2 = 31, duration: 3.4ms\n",
+            // day09, day13, day19, day20
+            "Part1: 11, duration: 1.2ms\nPart 2: 31, duration: 3.4ms\n",
+        ] {
+            assert!(answers.check(output).is_empty(), "{}", output);
+        }
+
+        let answers = Answers::parse("1: 5\n")?;
+        assert!(answers.check("Part12: 7\nPart1: 5\n").is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+mod answers;
+mod new_day;
+mod puzzle;
+mod validate;
+
+use answers::Answers;
+use puzzle::{Puzzle, DEFAULT_YEAR};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+fn main() -> std::io::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let Some(command) = args.get(1) else {
+        usage();
+    };
+    // Anything after `--` is passed through to the day binary
+    let (args, passthrough) = match args.iter().position(|a| a == "--") {
+        // `--` in place of the command
+        Some(i) if i < 2 => usage(),
+        Some(i) => (&args[2..i], &args[(i + 1)..]),
+        None => (&args[2..], &args[args.len()..]),
+    };
+    let mut args = args.to_vec();
+    let fix = take_flag(&mut args, "--fix");
+    let Some(file) = take_option(&mut args, "--file") else {
+        usage();
+    };
+    let Some(puzzle) = parse_puzzle(&args) else {
+        usage();
+    };
+
+    match command.as_str() {
+        "new-day" => new_day::new_day(puzzle),
+        // Extra arguments change what gets printed, so only plain runs are checked
+        "run" if passthrough.is_empty() => run_checked(puzzle),
+        "run" => run(puzzle, passthrough),
+        "repl" => {
+            if !REPL_PUZZLES.contains(&puzzle) {
+                eprintln!("{} has no interactive mode", puzzle.package_name());
+                std::process::exit(1);
+            }
+            let mut args = vec![String::from("--repl")];
+            args.extend_from_slice(passthrough);
+            run(puzzle, &args)
+        }
+        "compare" => {
+            if !COMPARE_PUZZLES.contains(&puzzle) {
+                eprintln!("{} has no alternative strategies", puzzle.package_name());
+                std::process::exit(1);
+            }
+            run(puzzle, &[String::from("--compare")])
+        }
+        "validate" => validate(puzzle, file.map(PathBuf::from), fix),
+        _ => usage(),
+    }
+}
+
+// Days whose binaries support `--repl`
+const REPL_PUZZLES: [Puzzle; 4] = [
+    Puzzle::new(2024, 6),
+    Puzzle::new(2024, 14),
+    Puzzle::new(2024, 15),
+    Puzzle::new(2024, 17),
+];
+
+// Days whose binaries support `--compare`
+const COMPARE_PUZZLES: [Puzzle; 5] = [
+    Puzzle::new(2024, 5),
+    Puzzle::new(2024, 6),
+    Puzzle::new(2024, 14),
+    Puzzle::new(2024, 17),
+    Puzzle::new(2024, 19),
+];
+
+fn usage() -> ! {
+    eprintln!("Usage: runner new-day [--year <year>] <day>");
+    eprintln!("       runner run [--year <year>] <day> [-- <args>...]");
+    eprintln!("       runner repl [--year <year>] <day> [-- [--script <file>] <args>...]");
+    eprintln!("       runner compare [--year <year>] <day>");
+    eprintln!("       runner validate [--year <year>] <day> [--file <file>] [--fix]");
+    std::process::exit(1);
+}
+
+// Build and run a day's binary from the workspace root so it finds its input
+fn run(puzzle: Puzzle, args: &[String]) -> std::io::Result<()> {
+    let status = Command::new("cargo")
+        .args(["run", "--release", "-p", &puzzle.package_name(), "--"])
+        .args(args)
+        .status()?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+// Run a day's binary and check what it prints against its recorded answers, if any
+fn run_checked(puzzle: Puzzle) -> std::io::Result<()> {
+    let path = puzzle.answers_path();
+    let Some(answers) = Answers::load(&path)? else {
+        return run(puzzle, &[]);
+    };
+
+    let output = Command::new("cargo")
+        .args(["run", "--release", "-p", &puzzle.package_name()])
+        .stderr(Stdio::inherit())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    print!("{}", stdout);
+    if !output.status.success() {
+        std::process::exit(output.status.code().unwrap_or(1));
+    }
+
+    let mismatches = answers.check(&stdout);
+    for mismatch in mismatches.iter() {
+        println!("{}: {}", path.display(), mismatch);
+    }
+    if !mismatches.is_empty() {
+        std::process::exit(1);
+    }
+    println!("Answers match {}", path.display());
+
+    Ok(())
+}
+
+// Check an input file against the day's grammar before trying to solve it
+fn validate(puzzle: Puzzle, file: Option<PathBuf>, fix: bool) -> std::io::Result<()> {
+    let Some(grammar) = validate::grammar(puzzle) else {
+        eprintln!("No input grammar for {}", puzzle.package_name());
+        std::process::exit(1);
+    };
+    let path = file.unwrap_or_else(|| puzzle.input_path());
+
+    let problems = validate::validate_file(&path, &grammar, fix)?;
+    for problem in problems.iter() {
+        println!("{}:{}", path.display(), problem);
+    }
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+    println!("{} is valid", path.display());
+
+    Ok(())
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| a != flag);
+    args.len() != len
+}
+
+// Remove `option <value>` from the arguments, returning None if the value is missing
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<Option<String>> {
+    let Some(i) = args.iter().position(|a| a == option) else {
+        return Some(None);
+    };
+    if i + 1 >= args.len() {
+        return None;
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+
+    Some(Some(value))
+}
+
+fn parse_puzzle(args: &[String]) -> Option<Puzzle> {
+    let mut year = DEFAULT_YEAR;
+    let mut day = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--year" {
+            year = parse_year(args.next()?)?;
+        } else if day.is_none() {
+            day = Some(parse_day(arg)?);
+        } else {
+            return None;
+        }
+    }
+
+    Some(Puzzle::new(year, day?))
+}
+
+fn parse_year(s: &str) -> Option<u32> {
+    let year = s.parse::<u32>().ok()?;
+    // The first Advent of Code was in 2015
+    (year >= 2015).then_some(year)
+}
+
+fn parse_day(s: &str) -> Option<u32> {
+    let day = s.trim_start_matches("day").parse::<u32>().ok()?;
+    (1..=25).contains(&day).then_some(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day("7"), Some(7));
+        assert_eq!(parse_day("07"), Some(7));
+        assert_eq!(parse_day("day25"), Some(25));
+        assert_eq!(parse_day("0"), None);
+        assert_eq!(parse_day("26"), None);
+        assert_eq!(parse_day("x"), None);
+    }
+
+    #[test]
+    fn test_take_option() {
+        let mut a = args("5 --file x.txt --fix");
+        assert_eq!(
+            take_option(&mut a, "--file"),
+            Some(Some(String::from("x.txt")))
+        );
+        assert!(take_flag(&mut a, "--fix"));
+        assert_eq!(a, args("5"));
+        assert_eq!(take_option(&mut a, "--file"), Some(None));
+        assert_eq!(take_option(&mut args("5 --file"), "--file"), None);
+    }
+
+    #[test]
+    fn test_parse_puzzle() {
+        assert_eq!(parse_puzzle(&args("5")), Some(Puzzle::new(DEFAULT_YEAR, 5)));
+        assert_eq!(
+            parse_puzzle(&args("--year 2023 day05")),
+            Some(Puzzle::new(2023, 5))
+        );
+        assert_eq!(
+            parse_puzzle(&args("5 --year 2016")),
+            Some(Puzzle::new(2016, 5))
+        );
+        assert_eq!(parse_puzzle(&args("--year 2014 5")), None);
+        assert_eq!(parse_puzzle(&args("--year")), None);
+        assert_eq!(parse_puzzle(&args("5 6")), None);
+        assert_eq!(parse_puzzle(&args("")), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::answers;
+use crate::puzzle::Puzzle;
+
+const MAIN_TEMPLATE: &str = include_str!("../templates/main.rs.tmpl");
+
+// Generate a new day crate, register it in the workspace and create its placeholder inputs
+// and answers.
+// Expects to be run from the workspace root, like the day binaries themselves.
+pub fn new_day(puzzle: Puzzle) -> std::io::Result<()> {
+    let name = puzzle.package_name();
+    let crate_dir = puzzle.crate_dir();
+    if crate_dir.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", crate_dir.display()),
+        ));
+    }
+
+    let manifest = fs::read_to_string("Cargo.toml")?;
+    let manifest = add_workspace_member(&manifest, &crate_dir.to_string_lossy())?;
+
+    fs::create_dir_all(crate_dir.join("src"))?;
+    fs::write(crate_dir.join("Cargo.toml"), crate_manifest(&name))?;
+    fs::write(
+        crate_dir.join("src").join("main.rs"),
+        render_main(
+            &puzzle.input_path().to_string_lossy(),
+            &puzzle.test_input_path_from_crate().to_string_lossy(),
+            &puzzle.test_answers_path_from_crate().to_string_lossy(),
+        ),
+    )?;
+    fs::write("Cargo.toml", manifest)?;
+
+    create_placeholder(&puzzle.test_input_path(), "")?;
+    create_placeholder(&puzzle.input_path(), "")?;
+    create_placeholder(&puzzle.answers_path(), &placeholder_answers())?;
+    create_placeholder(&puzzle.test_answers_path(), &placeholder_answers())?;
+
+    println!("Created {} in {}", name, crate_dir.display());
+
+    Ok(())
+}
+
+fn crate_manifest(name: &str) -> String {
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+        name
+    )
+}
+
+fn render_main(input: &str, test_input: &str, test_answers: &str) -> String {
+    MAIN_TEMPLATE
+        .replace("{{input}}", input)
+        .replace("{{test_input}}", test_input)
+        .replace("{{test_answers}}", test_answers)
+}
+
+// Every part unknown until it's solved
+fn placeholder_answers() -> String {
+    format!("1: {}\n2: {}\n", answers::UNKNOWN, answers::UNKNOWN)
+}
+
+fn create_placeholder(path: &Path, contents: &str) -> std::io::Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+// Insert `member` into the `members` list of the workspace manifest, keeping it sorted.
+fn add_workspace_member(manifest: &str, member: &str) -> std::io::Result<String> {
+    let invalid = || Error::new(ErrorKind::InvalidData, "no workspace members list found");
+    let start = manifest.find("members = [").ok_or_else(invalid)? + "members = [".len();
+    let end = start + manifest[start..].find(']').ok_or_else(invalid)?;
+
+    let mut members = manifest[start..end]
+        .split(',')
+        .map(|s| s.trim().trim_matches('"'))
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    if !members.contains(&member) {
+        members.push(member);
+    }
+    members.sort();
+
+    let mut result = String::from(&manifest[..start]);
+    result.push('\n');
+    for m in members {
+        result.push_str(&format!("    \"{}\",\n", m));
+    }
+    result.push_str(&manifest[end..]);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "[workspace]\nresolver = \"2\"\n\nmembers = [\n    \"day01\",\n    \"day02\",\n    \"runner\",\n]\n";
+
+    #[test]
+    fn test_add_workspace_member() -> std::io::Result<()> {
+        let manifest = add_workspace_member(MANIFEST, "day03")?;
+        assert_eq!(
+            manifest,
+            "[workspace]\nresolver = \"2\"\n\nmembers = [\n    \"day01\",\n    \"day02\",\n    \"day03\",\n    \"runner\",\n]\n"
+        );
+        assert_eq!(add_workspace_member(&manifest, "day03")?, manifest);
+        assert!(add_workspace_member("[workspace]\n", "day03").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_main() {
+        let main = render_main(
+            "input/day26.txt",
+            "../test_input/day26test.txt",
+            "../answers/2024/day26test.txt",
+        );
+        assert!(main.contains("parse_input(\"input/day26.txt\")"));
+        assert!(main.contains("parse_input(\"../test_input/day26test.txt\")"));
+        assert!(main.contains("read_to_string(\"../answers/2024/day26test.txt\")"));
+        assert!(!main.contains("{{"));
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+// 2024 predates multi-year support, so its crates and inputs live at the top level.
+pub const DEFAULT_YEAR: u32 = 2024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Puzzle {
+    pub year: u32,
+    pub day: u32,
+}
+
+impl Puzzle {
+    pub const fn new(year: u32, day: u32) -> Self {
+        Self { year, day }
+    }
+
+    fn day_name(&self) -> String {
+        format!("day{:02}", self.day)
+    }
+
+    fn is_default_year(&self) -> bool {
+        self.year == DEFAULT_YEAR
+    }
+
+    pub fn package_name(&self) -> String {
+        if self.is_default_year() {
+            self.day_name()
+        } else {
+            format!("{}_{}", self.day_name(), self.year)
+        }
+    }
+
+    // Relative to the workspace root
+    pub fn crate_dir(&self) -> PathBuf {
+        if self.is_default_year() {
+            PathBuf::from(self.day_name())
+        } else {
+            PathBuf::from(self.year.to_string()).join(self.day_name())
+        }
+    }
+
+    fn year_dir(&self, base: &str) -> PathBuf {
+        if self.is_default_year() {
+            PathBuf::from(base)
+        } else {
+            PathBuf::from(base).join(self.year.to_string())
+        }
+    }
+
+    // Relative to the workspace root, which is where the day binaries are run from
+    pub fn input_path(&self) -> PathBuf {
+        self.year_dir("input")
+            .join(format!("{}.txt", self.day_name()))
+    }
+
+    // Relative to the workspace root. Unlike the inputs this is keyed by year for 2024 too,
+    // as there was no registry before multi-year support.
+    pub fn answers_path(&self) -> PathBuf {
+        PathBuf::from("answers")
+            .join(self.year.to_string())
+            .join(format!("{}.txt", self.day_name()))
+    }
+
+    // The answers for the example in the test input, relative to the workspace root
+    pub fn test_answers_path(&self) -> PathBuf {
+        PathBuf::from("answers")
+            .join(self.year.to_string())
+            .join(format!("{}test.txt", self.day_name()))
+    }
+
+    // Relative to the workspace root
+    pub fn test_input_path(&self) -> PathBuf {
+        self.year_dir("test_input")
+            .join(format!("{}test.txt", self.day_name()))
+    }
+
+    // Relative to the crate directory, which is where `cargo test` runs the tests from
+    pub fn test_input_path_from_crate(&self) -> PathBuf {
+        self.relative_to_crate(self.test_input_path())
+    }
+
+    pub fn test_answers_path_from_crate(&self) -> PathBuf {
+        self.relative_to_crate(self.test_answers_path())
+    }
+
+    fn relative_to_crate(&self, path: PathBuf) -> PathBuf {
+        let mut result = PathBuf::new();
+        for _ in self.crate_dir().components() {
+            result.push("..");
+        }
+        result.join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_year_paths() {
+        let puzzle = Puzzle::new(DEFAULT_YEAR, 5);
+        assert_eq!(puzzle.package_name(), "day05");
+        assert_eq!(puzzle.crate_dir(), PathBuf::from("day05"));
+        assert_eq!(puzzle.input_path(), PathBuf::from("input/day05.txt"));
+        assert_eq!(
+            puzzle.answers_path(),
+            PathBuf::from("answers/2024/day05.txt")
+        );
+        assert_eq!(
+            puzzle.test_input_path_from_crate(),
+            PathBuf::from("../test_input/day05test.txt")
+        );
+    }
+
+    #[test]
+    fn test_other_year_paths() {
+        let puzzle = Puzzle::new(2023, 12);
+        assert_eq!(puzzle.package_name(), "day12_2023");
+        assert_eq!(puzzle.crate_dir(), PathBuf::from("2023/day12"));
+        assert_eq!(puzzle.input_path(), PathBuf::from("input/2023/day12.txt"));
+        assert_eq!(
+            puzzle.answers_path(),
+            PathBuf::from("answers/2023/day12.txt")
+        );
+        assert_eq!(
+            puzzle.test_input_path_from_crate(),
+            PathBuf::from("../../test_input/2023/day12test.txt")
+        );
+        assert_eq!(
+            puzzle.test_answers_path_from_crate(),
+            PathBuf::from("../../answers/2023/day12test.txt")
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::puzzle::Puzzle;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub line: usize,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(line: usize, column: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}: {}", self.line, column, self.message),
+            None => write!(f, "{}: {}", self.line, self.message),
+        }
+    }
+}
+
+pub enum Rule {
+    // One or more lines, each matching the pattern
+    Lines(&'static str),
+    // One or more lines of equal length, made up of characters matching the pattern
+    Grid(&'static str),
+    // Exactly one line per pattern, in order
+    Fixed(&'static [&'static str]),
+}
+
+pub struct Section {
+    name: &'static str,
+    rule: Rule,
+}
+
+fn section(name: &'static str, rule: Rule) -> Section {
+    Section { name, rule }
+}
+
+pub enum Grammar {
+    // Anything goes, e.g. day03's corrupted memory
+    Any,
+    // These sections in order, separated by single blank lines
+    Sections(Vec<Section>),
+    // The same section any number of times, separated by single blank lines
+    Repeated(Section),
+}
+
+pub fn grammar(puzzle: Puzzle) -> Option<Grammar> {
+    use Grammar::*;
+    use Rule::*;
+
+    if puzzle.year != 2024 {
+        return None;
+    }
+    let grammar = match puzzle.day {
+        1 => Sections(vec![section("location lists", Lines(r"\d+ +\d+"))]),
+        2 => Sections(vec![section("reports", Lines(r"\d+( \d+)*"))]),
+        3 => Any,
+        4 => Sections(vec![section("word search", Grid("[XMAS]"))]),
+        5 => Sections(vec![
+            section("ordering rules", Lines(r"\d+\|\d+")),
+            section("updates", Lines(r"\d+(,\d+)*")),
+        ]),
+        6 => Sections(vec![section("map", Grid(r"[.#\^>v<]"))]),
+        7 => Sections(vec![section("equations", Lines(r"\d+:( \d+)+"))]),
+        8 => Sections(vec![section("map", Grid("[.a-zA-Z0-9]"))]),
+        9 => Sections(vec![section("disk map", Fixed(&[r"\d+"]))]),
+        10 => Sections(vec![section("topographic map", Grid("[0-9.]"))]),
+        11 => Sections(vec![section("stones", Fixed(&[r"\d+( \d+)*"]))]),
+        12 => Sections(vec![section("garden", Grid("[A-Z]"))]),
+        13 => Repeated(section(
+            "claw machine",
+            Fixed(&[
+                r"Button A: X\+\d+, Y\+\d+",
+                r"Button B: X\+\d+, Y\+\d+",
+                r"Prize: X=\d+, Y=\d+",
+            ]),
+        )),
+        14 => Sections(vec![section(
+            "robots",
+            Lines(r"p=-?\d+,-?\d+ v=-?\d+,-?\d+"),
+        )]),
+        15 => Sections(vec![
+            section("warehouse", Grid("[#.O@]")),
+            section("moves", Lines(r"[<>\^v]+")),
+        ]),
+        16 => Sections(vec![section("maze", Grid("[#.SE]"))]),
+        17 => Sections(vec![
+            section(
+                "registers",
+                Fixed(&[r"Register A: \d+", r"Register B: \d+", r"Register C: \d+"]),
+            ),
+            section("program", Fixed(&["Program: [0-7](,[0-7])*"])),
+        ]),
+        18 => Sections(vec![section("bytes", Lines(r"\d+,\d+"))]),
+        19 => Sections(vec![
+            section("towels", Fixed(&["[wubrg]+(, [wubrg]+)*"])),
+            section("designs", Lines("[wubrg]+")),
+        ]),
+        20 => Sections(vec![section("racetrack", Grid("[#.SE]"))]),
+        21 => Sections(vec![section("codes", Lines(r"\d{3}A"))]),
+        22 => Sections(vec![section("secret numbers", Lines(r"\d+"))]),
+        23 => Sections(vec![section("connections", Lines("[a-z]{2}-[a-z]{2}"))]),
+        24 => Sections(vec![
+            section("initial wires", Lines(r"[xy]\d{2}: [01]")),
+            section(
+                "gates",
+                Lines("[a-z0-9]{3} (AND|OR|XOR) [a-z0-9]{3} -> [a-z0-9]{3}"),
+            ),
+        ]),
+        25 => Repeated(section("schematic", Fixed(&["[#.]{5}"; 7]))),
+        _ => return None,
+    };
+
+    Some(grammar)
+}
+
+// Validate the file at `path`, optionally rewriting it with normalised line endings and
+// whitespace first. Returns the problems found.
+pub fn validate_file(path: &Path, grammar: &Grammar, fix: bool) -> std::io::Result<Vec<Problem>> {
+    let mut contents = fs::read_to_string(path)?;
+    if fix {
+        let normalised = normalise(&contents);
+        if normalised != contents {
+            fs::write(path, &normalised)?;
+            println!("Normalised {}", path.display());
+        }
+        contents = normalised;
+    }
+
+    Ok(validate(&contents, grammar))
+}
+
+// Strip carriage returns, trailing whitespace and leading/trailing/repeated blank lines
+pub fn normalise(contents: &str) -> String {
+    let mut result = String::new();
+    let mut pending_blank = false;
+    for line in contents.lines().map(str::trim_end) {
+        if line.is_empty() {
+            pending_blank = !result.is_empty();
+            continue;
+        }
+        if pending_blank {
+            result.push('\n');
+            pending_blank = false;
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    result
+}
+
+pub fn validate(contents: &str, grammar: &Grammar) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let raw_lines = contents.split_terminator('\n').collect::<Vec<_>>();
+    let mut lines = Vec::new();
+    for (i, raw) in raw_lines.iter().enumerate() {
+        let line = raw.strip_suffix('\r').unwrap_or(raw);
+        if line.len() != raw.len() {
+            problems.push(Problem::new(i + 1, Some(raw.len()), "CRLF line ending"));
+        }
+        let trimmed = line.trim_end();
+        if trimmed.len() != line.len() {
+            problems.push(Problem::new(
+                i + 1,
+                Some(trimmed.len() + 1),
+                "trailing whitespace",
+            ));
+        }
+        lines.push(trimmed);
+    }
+
+    let Some(first) = lines.iter().position(|l| !l.is_empty()) else {
+        problems.push(Problem::new(1, None, "file is empty"));
+        return problems;
+    };
+    let last = lines.iter().rposition(|l| !l.is_empty()).unwrap();
+    if first > 0 {
+        problems.push(Problem::new(1, None, "leading blank line"));
+    }
+    if last + 1 < lines.len() {
+        problems.push(Problem::new(last + 2, None, "trailing blank line"));
+    }
+
+    // Split into blank line separated blocks of (first line number, lines)
+    let mut blocks: Vec<(usize, Vec<&str>)> = Vec::new();
+    let mut blank_run = 0;
+    for (i, &line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run == 2 {
+                problems.push(Problem::new(i + 1, None, "more than one blank line"));
+            }
+            continue;
+        }
+        if blank_run > 0 || blocks.is_empty() {
+            blocks.push((i + 1, Vec::new()));
+        }
+        blank_run = 0;
+        blocks.last_mut().unwrap().1.push(line);
+    }
+
+    match grammar {
+        Grammar::Any => {}
+        Grammar::Sections(sections) => {
+            for ((start, block), section) in blocks.iter().zip(sections.iter()) {
+                check_section(*start, block, section, &mut problems);
+            }
+            if let Some((start, _)) = blocks.get(sections.len()) {
+                problems.push(Problem::new(
+                    *start,
+                    None,
+                    format!("unexpected section, expected {}", sections.len()),
+                ));
+            }
+            if let Some(section) = sections.get(blocks.len()) {
+                problems.push(Problem::new(
+                    last + 1,
+                    None,
+                    format!("missing {} section", section.name),
+                ));
+            }
+        }
+        Grammar::Repeated(section) => {
+            for (start, block) in blocks.iter() {
+                check_section(*start, block, section, &mut problems);
+            }
+        }
+    }
+    problems.sort_by_key(|p| (p.line, p.column));
+
+    problems
+}
+
+fn check_section(start: usize, lines: &[&str], section: &Section, problems: &mut Vec<Problem>) {
+    match &section.rule {
+        Rule::Lines(pattern) => {
+            let re = full_match(pattern);
+            for (i, line) in lines.iter().enumerate() {
+                if !re.is_match(line) {
+                    problems.push(Problem::new(
+                        start + i,
+                        None,
+                        format!("not a valid {} line", section.name),
+                    ));
+                }
+            }
+        }
+        Rule::Grid(pattern) => {
+            let re = full_match(pattern);
+            let width = lines[0].chars().count();
+            for (i, line) in lines.iter().enumerate() {
+                if line.chars().count() != width {
+                    problems.push(Problem::new(
+                        start + i,
+                        None,
+                        format!(
+                            "{} row is {} wide, expected {}",
+                            section.name,
+                            line.chars().count(),
+                            width
+                        ),
+                    ));
+                }
+                let mut buf = [0; 4];
+                if let Some((col, c)) = line
+                    .chars()
+                    .enumerate()
+                    .find(|(_, c)| !re.is_match(c.encode_utf8(&mut buf)))
+                {
+                    problems.push(Problem::new(
+                        start + i,
+                        Some(col + 1),
+                        format!("unexpected {:?} in {}", c, section.name),
+                    ));
+                }
+            }
+        }
+        Rule::Fixed(patterns) => {
+            for (i, (line, pattern)) in lines.iter().zip(patterns.iter()).enumerate() {
+                if !full_match(pattern).is_match(line) {
+                    problems.push(Problem::new(
+                        start + i,
+                        None,
+                        format!("not a valid {} line", section.name),
+                    ));
+                }
+            }
+            if lines.len() != patterns.len() {
+                problems.push(Problem::new(
+                    start,
+                    None,
+                    format!(
+                        "{} has {} lines, expected {}",
+                        section.name,
+                        lines.len(),
+                        patterns.len()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn full_match(pattern: &str) -> Regex {
+    Regex::new(&format!("^(?:{})$", pattern)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: [(u32, &str); 31] = [
+        (1, "day01test.txt"),
+        (2, "day02test.txt"),
+        (3, "day03test.txt"),
+        (3, "day03part2test.txt"),
+        (4, "day04test.txt"),
+        (5, "day05test.txt"),
+        (6, "day06test.txt"),
+        (7, "day07test.txt"),
+        (8, "day08test.txt"),
+        (9, "day09test.txt"),
+        (10, "day10test.txt"),
+        (11, "day11test.txt"),
+        (11, "day11test2.txt"),
+        (12, "day12test.txt"),
+        (13, "day13test.txt"),
+        (14, "day14test.txt"),
+        (15, "day15test1.txt"),
+        (15, "day15test2.txt"),
+        (15, "day15test3.txt"),
+        (16, "day16test.txt"),
+        (17, "day17test.txt"),
+        (18, "day18test.txt"),
+        (19, "day19test.txt"),
+        (20, "day20test.txt"),
+        (21, "day21.txt"),
+        (22, "day22test.txt"),
+        (23, "day23.txt"),
+        (24, "day24.txt"),
+        (24, "day24simple.txt"),
+        (25, "day25.txt"),
+        (10, "day10test_simple.txt"),
+    ];
+
+    #[test]
+    fn test_examples_are_valid() -> std::io::Result<()> {
+        for (day, file) in EXAMPLES {
+            let grammar = grammar(Puzzle::new(2024, day)).unwrap();
+            let contents = fs::read_to_string(Path::new("../test_input").join(file))?;
+            assert_eq!(validate(&contents, &grammar), vec![], "{}", file);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_whitespace_problems() {
+        let grammar = grammar(Puzzle::new(2024, 1)).unwrap();
+        assert_eq!(
+            validate("\n3   4\r\n4   3 \n\n", &grammar),
+            vec![
+                Problem::new(1, None, "leading blank line"),
+                Problem::new(2, Some(6), "CRLF line ending"),
+                Problem::new(3, Some(6), "trailing whitespace"),
+                Problem::new(4, None, "trailing blank line"),
+            ]
+        );
+        assert_eq!(normalise("\n3   4\r\n4   3 \n\n"), "3   4\n4   3\n");
+    }
+
+    #[test]
+    fn test_section_problems() {
+        let grammar = grammar(Puzzle::new(2024, 5)).unwrap();
+        assert_eq!(
+            validate("47|53\n97|13\n\n\n75,47\n75;47\n", &grammar),
+            vec![
+                Problem::new(4, None, "more than one blank line"),
+                Problem::new(6, None, "not a valid updates line"),
+            ]
+        );
+        assert_eq!(
+            validate("47|53\n97|13\n", &grammar),
+            vec![Problem::new(2, None, "missing updates section")]
+        );
+
+        let grammar = super::grammar(Puzzle::new(2024, 6)).unwrap();
+        assert_eq!(
+            validate("..#\n.^\n.x.\n", &grammar),
+            vec![
+                Problem::new(2, None, "map row is 2 wide, expected 3"),
+                Problem::new(3, Some(2), "unexpected 'x' in map"),
+            ]
+        );
+    }
+}